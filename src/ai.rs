@@ -0,0 +1,375 @@
+//! Computer players. A `Policy` picks the next chain for a game, `Autoplay` plays whole games
+//! with one, without a window. Searches never peek at the dots that will really fall in:
+//! every line of play they try draws its own from a seed of the policy.
+
+use std::cmp::Reverse;
+use std::fmt;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+use crate::engine::{Chain, Outcome, Session, Turn};
+use crate::replay::{Action, Replay};
+
+/// Chains looked at in a position, see `Board::chains`.
+pub const SEARCH: usize = 400;
+/// Chains a rollout picks its random moves from.
+const ROLLOUT_SEARCH: usize = 50;
+/// Worth of a dot cleared towards a goal, on top of the points it scored.
+const GOAL_POINTS: u64 = 10;
+/// Worth of winning a level, more than any score given up for it.
+const WIN_POINTS: u64 = 1_000_000;
+
+/// How good a position is to a search: the score, and for levels how close the goals are.
+pub fn value(session: &Session) -> u64 {
+    let goals: u64 = session.goals().iter()
+        .map(|goal| u64::from(goal.target - session.remaining(goal)) * GOAL_POINTS)
+        .sum();
+    let won = if session.outcome() == Outcome::Won { WIN_POINTS } else { 0 };
+
+    session.score().saturating_add(goals).saturating_add(won)
+}
+
+/// The actions that draw `chain` and commit it.
+pub fn actions(chain: &Chain) -> Vec<Action> {
+    let cells = chain.cells();
+
+    cells.first().map(|&first| Action::Start(first)).into_iter()
+        .chain(cells.iter().skip(1).map(|&cell| Action::Extend(cell)))
+        .chain(Some(Action::Commit))
+        .collect()
+}
+
+/// Draws `chain` on the board and commits it.
+pub fn play_chain(session: &mut Session, chain: &Chain) -> Option<Turn> {
+    actions(chain).into_iter().fold(None, |_, action| action.apply(session).1)
+}
+
+/// Every chain on the board and the game after it, with the dots to come drawn from `seed`.
+fn successors(session: &Session, seed: u64) -> Vec<(Chain, Session)> {
+    session.board().chains(SEARCH).into_iter()
+        .filter_map(|chain| {
+            let mut next = session.reseeded(seed);
+            play_chain(&mut next, &chain)?;
+            Some((chain, next))
+        })
+        .collect()
+}
+
+/// Best first; ties keep the order the chains were found in.
+fn rank(options: &mut [(Chain, Session)]) {
+    options.sort_by_key(|(_, session)| Reverse(value(session)));
+}
+
+/// Picks the chains to draw.
+pub trait Policy: fmt::Debug {
+    fn name(&self) -> &'static str;
+
+    /// The chain to draw next, `None` when the game is over or there is none.
+    fn choose(&mut self, session: &Session) -> Option<Chain>;
+}
+
+/// Takes the chain worth the most right away.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Greedy;
+
+impl Policy for Greedy {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn choose(&mut self, session: &Session) -> Option<Chain> {
+        if session.is_over() {
+            return None;
+        }
+
+        let mut options = successors(session, 0);
+        rank(&mut options);
+
+        options.into_iter().next().map(|(chain, _)| chain)
+    }
+}
+
+/// Follows the `width` best lines of play `depth` turns deep, and takes the first chain of
+/// the line that ends best.
+#[derive(Debug, Clone)]
+pub struct Beam {
+    width: usize,
+    depth: usize,
+    rng: Pcg32,
+}
+
+impl Beam {
+    pub fn new(width: usize, depth: usize, seed: u64) -> Self {
+        Beam { width: width.max(1), depth: depth.max(1), rng: Pcg32::seed_from_u64(seed) }
+    }
+}
+
+impl Policy for Beam {
+    fn name(&self) -> &'static str {
+        "beam"
+    }
+
+    fn choose(&mut self, session: &Session) -> Option<Chain> {
+        if session.is_over() {
+            return None;
+        }
+
+        let mut beam = successors(session, self.rng.gen());
+        rank(&mut beam);
+        beam.truncate(self.width);
+
+        for _ in 1..self.depth {
+            let seed = self.rng.gen();
+            let mut next = Vec::new();
+
+            for (first, line) in beam {
+                let after = if line.is_over() { Vec::new() } else { successors(&line, seed) };
+
+                if after.is_empty() {
+                    next.push((first, line));
+                } else {
+                    next.extend(after.into_iter().map(|(_, session)| (first.clone(), session)));
+                }
+            }
+
+            rank(&mut next);
+            next.truncate(self.width);
+            beam = next;
+        }
+
+        beam.into_iter().next().map(|(chain, _)| chain)
+    }
+}
+
+/// Tries the `candidates` best chains, plays `rollouts` random games of `depth` turns on from
+/// each, and takes the chain whose games end best on average.
+#[derive(Debug, Clone)]
+pub struct MonteCarlo {
+    candidates: usize,
+    rollouts: usize,
+    depth: usize,
+    rng: Pcg32,
+}
+
+impl MonteCarlo {
+    pub fn new(candidates: usize, rollouts: usize, depth: usize, seed: u64) -> Self {
+        MonteCarlo {
+            candidates: candidates.max(1),
+            rollouts: rollouts.max(1),
+            depth,
+            rng: Pcg32::seed_from_u64(seed),
+        }
+    }
+
+    /// Plays random chains on a copy of `session` and returns how it ends.
+    fn rollout(&mut self, session: &Session) -> u64 {
+        let mut line = session.reseeded(self.rng.gen());
+
+        for _ in 0..self.depth {
+            let chains = line.board().chains(ROLLOUT_SEARCH);
+            if line.is_over() || chains.is_empty() {
+                break;
+            }
+
+            let chain = &chains[self.rng.gen_range(0, chains.len())];
+            play_chain(&mut line, chain);
+        }
+
+        value(&line)
+    }
+}
+
+impl Policy for MonteCarlo {
+    fn name(&self) -> &'static str {
+        "montecarlo"
+    }
+
+    fn choose(&mut self, session: &Session) -> Option<Chain> {
+        if session.is_over() {
+            return None;
+        }
+
+        let mut options = successors(session, self.rng.gen());
+        rank(&mut options);
+        options.truncate(self.candidates);
+
+        let mut best: Option<(Chain, u64)> = None;
+        for (chain, after) in options {
+            let total = (0..self.rollouts).fold(0u64, |total, _| total.saturating_add(self.rollout(&after)));
+
+            if best.as_ref().is_none_or(|(_, best)| total > *best) {
+                best = Some((chain, total));
+            }
+        }
+
+        best.map(|(chain, _)| chain)
+    }
+}
+
+/// How a game played by a policy went.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Playthrough {
+    pub score: u64,
+    pub moves: u32,
+    /// Turns that closed a loop.
+    pub loops: u32,
+    /// Turns after which the board had no move left and was shuffled.
+    pub shuffles: u32,
+    /// Whether the game stopped on a board without any chain, even shuffled.
+    pub stuck: bool,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+/// Plays whole games with a policy.
+#[derive(Debug, Clone)]
+pub struct Autoplay {
+    /// Game time each turn takes.
+    pub move_time: Duration,
+    /// Turns after which a game stops, for endless games.
+    pub max_moves: u32,
+}
+
+impl Default for Autoplay {
+    fn default() -> Self {
+        Autoplay { move_time: Duration::from_secs(2), max_moves: 200 }
+    }
+}
+
+impl Autoplay {
+    pub fn play(&self, session: &mut Session, policy: &mut dyn Policy) -> Playthrough {
+        self.run(session, policy, |_, _| ())
+    }
+
+    /// Plays the game of `replay` and records it there, for a demo to watch with `--replay`.
+    pub fn record(&self, replay: &mut Replay, policy: &mut dyn Policy) -> Playthrough {
+        let mut session = replay.session();
+        let playthrough = self.run(&mut session, policy, |at, action| replay.record(at, action));
+        replay.finish(&session);
+
+        playthrough
+    }
+
+    fn run<F: FnMut(Duration, Action)>(&self, session: &mut Session, policy: &mut dyn Policy, mut record: F) -> Playthrough {
+        let (mut loops, mut shuffles, mut stuck) = (0, 0, false);
+
+        while !session.is_over() && session.moves() < self.max_moves {
+            session.tick(self.move_time);
+
+            let chain = match policy.choose(session) {
+                Some(chain) => chain,
+                None => {
+                    stuck = !session.is_over();
+                    break;
+                },
+            };
+
+            for action in actions(&chain) {
+                let (_, turn) = action.apply(session);
+                record(session.elapsed(), action);
+
+                if let Some(turn) = turn {
+                    loops += u32::from(turn.clear.closed);
+                    shuffles += u32::from(!turn.clear.shuffled.is_empty());
+                }
+            }
+        }
+
+        Playthrough {
+            score: session.score(),
+            moves: session.moves(),
+            loops,
+            shuffles,
+            stuck,
+            outcome: session.outcome(),
+            elapsed: session.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::board::test::board_from_rows;
+    use crate::engine::{Color, Goal, Mode};
+    use crate::level::Level;
+
+    fn policies() -> Vec<Box<dyn Policy>> {
+        vec![Box::new(Greedy), Box::new(Beam::new(3, 2, 1)), Box::new(MonteCarlo::new(3, 4, 3, 1))]
+    }
+
+    #[test]
+    fn greedy_takes_the_most_points() {
+        let session = Session::with_board(board_from_rows(&["RRG", "RRB", "BGB"]), Mode::Endless);
+        let chain = Greedy.choose(&session).unwrap();
+
+        assert!(chain.is_closed());
+        assert_eq!(chain.color(), Some(Color::Red));
+    }
+
+    #[test]
+    fn policies_work_towards_goals() {
+        let board = board_from_rows(&["RRRG", "BGPC", "BCPG"]);
+        let session = Session::with_board(board, Mode::Moves(1)).with_goals(vec![Goal::new(Color::Blue, 2)]);
+
+        policies().iter_mut().for_each(|policy| {
+            assert_eq!(policy.choose(&session).unwrap().color(), Some(Color::Blue), "{}", policy.name());
+        });
+    }
+
+    #[test]
+    fn policies_play_whole_games() {
+        let level = Level::parse("width = 5\nheight = 5\nmoves = 8\n").unwrap();
+
+        policies().iter_mut().for_each(|policy| {
+            let playthrough = Autoplay::default().play(&mut level.session(3), policy.as_mut());
+
+            assert_eq!(playthrough.moves, 8, "{}", policy.name());
+            assert_eq!(playthrough.outcome, Outcome::Finished);
+            assert!(playthrough.score >= 16);
+            assert!(!playthrough.stuck);
+        });
+    }
+
+    #[test]
+    fn endless_and_timed_games_stop() {
+        let autoplay = Autoplay { move_time: Duration::from_secs(3), max_moves: 12 };
+
+        let endless = Level::parse("width = 4\nheight = 4\n").unwrap();
+        assert_eq!(autoplay.play(&mut endless.session(1), &mut Greedy).moves, 12);
+
+        let timed = Level::parse("width = 4\nheight = 4\ntime = 10\n").unwrap();
+        let playthrough = autoplay.play(&mut timed.session(1), &mut Greedy);
+        assert_eq!(playthrough.moves, 3);
+        assert_eq!(playthrough.outcome, Outcome::Finished);
+    }
+
+    #[test]
+    fn stuck_boards_end_the_game() {
+        let mut session = Session::with_board(board_from_rows(&["RG#", "#BP"]), Mode::Endless);
+        let playthrough = Autoplay::default().play(&mut session, &mut Greedy);
+
+        assert!(playthrough.stuck);
+        assert_eq!(playthrough.moves, 0);
+    }
+
+    #[test]
+    fn recorded_games_replay() {
+        let level = Level::parse("width = 5\nheight = 5\nmoves = 6\nloop = \"bombs\"\n").unwrap();
+        let mut replay = Replay::new(level, 9);
+        let playthrough = Autoplay::default().record(&mut replay, &mut Beam::new(2, 2, 4));
+
+        assert_eq!(replay.score, playthrough.score);
+        assert!(replay.verify());
+    }
+
+    #[test]
+    fn same_seed_same_choices() {
+        let level = Level::parse("width = 5\nheight = 5\nmoves = 5\n").unwrap();
+        let play = |seed| Autoplay::default().play(&mut level.session(2), &mut MonteCarlo::new(3, 3, 2, seed));
+
+        assert_eq!(play(5), play(5));
+    }
+}
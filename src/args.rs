@@ -0,0 +1,102 @@
+//! Command line values both `cats` and `cats-sim` take. Errors end with the `usage` passed in,
+//! so each binary prints its own.
+
+use std::time::Duration;
+
+use crate::engine::board::{MIN_SIZE, MAX_SIZE};
+use crate::engine::{Color, Mode};
+
+/// The value after flag `name`, parsed.
+pub fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>, usage: &str) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for `{}`\n{}", name, usage))?;
+
+    value.parse().map_err(|_| format!("invalid value `{}` for `{}`\n{}", value, name, usage))
+}
+
+/// `<width>x<height>`, each side within the board limits.
+pub fn parse_size(name: &str, value: Option<String>, usage: &str) -> Result<(usize, usize), String> {
+    let value: String = parse_value(name, value, usage)?;
+    let invalid = || format!("invalid value `{}` for `{}`, expected e.g. 6x6\n{}", value, name, usage);
+
+    let mut parts = value.splitn(2, 'x');
+    let width: usize = parts.next().and_then(|part| part.parse().ok()).ok_or_else(invalid)?;
+    let height: usize = parts.next().and_then(|part| part.parse().ok()).ok_or_else(invalid)?;
+
+    let range = MIN_SIZE..=MAX_SIZE;
+    if !range.contains(&width) || !range.contains(&height) {
+        return Err(format!("board sides must be between {} and {}\n{}", MIN_SIZE, MAX_SIZE, usage));
+    }
+
+    Ok((width, height))
+}
+
+/// Either a number of colors, taken in sprite sheet order, or a comma separated list of names.
+pub fn parse_colors(name: &str, value: Option<String>, usage: &str) -> Result<Vec<Color>, String> {
+    let value: String = parse_value(name, value, usage)?;
+
+    let colors = match value.parse::<usize>() {
+        Ok(count) if count <= Color::ALL.len() => Color::ALL[..count].to_vec(),
+        Ok(_) => return Err(format!("there are only {} colors\n{}", Color::ALL.len(), usage)),
+        Err(_) => value.split(',')
+            .map(|color| color.trim().parse::<Color>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|message| format!("{}\n{}", message, usage))?,
+    };
+
+    if colors.len() < 2 {
+        return Err(format!("a game needs at least 2 colors\n{}", usage));
+    }
+
+    if colors.iter().enumerate().any(|(index, color)| colors[..index].contains(color)) {
+        return Err(format!("colors in `{}` must not repeat\n{}", value, usage));
+    }
+
+    Ok(colors)
+}
+
+/// The mode of `--moves` or `--time`, given as `name`. Only one of them can be given, so
+/// `current` has to be endless still.
+pub fn parse_mode(name: &str, value: Option<String>, current: Mode, usage: &str) -> Result<Mode, String> {
+    let mode = if name == "--moves" {
+        match parse_value(name, value, usage)? {
+            0 => return Err(format!("a game needs at least one move\n{}", usage)),
+            moves => Mode::Moves(moves),
+        }
+    } else {
+        match parse_value(name, value, usage)? {
+            0 => return Err(format!("a game needs at least one second\n{}", usage)),
+            seconds => Mode::Timed(Duration::from_secs(seconds)),
+        }
+    };
+
+    if current != Mode::Endless {
+        return Err(format!("only one of `--moves` and `--time` can be given\n{}", usage));
+    }
+
+    Ok(mode)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn value(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn errors_end_with_the_usage() {
+        assert_eq!(parse_value::<u32>("--seed", None, "usage: x"), Err("missing value for `--seed`\nusage: x".to_string()));
+        assert!(parse_size("--size", value("1x5"), "usage: x").unwrap_err().ends_with("\nusage: x"));
+        assert!(parse_colors("--colors", value("red"), "usage: x").unwrap_err().ends_with("\nusage: x"));
+    }
+
+    #[test]
+    fn parses_modes() {
+        assert_eq!(parse_mode("--moves", value("5"), Mode::Endless, ""), Ok(Mode::Moves(5)));
+        assert_eq!(parse_mode("--time", value("30"), Mode::Endless, ""), Ok(Mode::Timed(Duration::from_secs(30))));
+        assert!(parse_mode("--moves", value("0"), Mode::Endless, "").is_err());
+        assert!(parse_mode("--time", value("0"), Mode::Endless, "").is_err());
+        assert!(parse_mode("--time", value("30"), Mode::Moves(5), "").is_err());
+    }
+}
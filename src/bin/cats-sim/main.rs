@@ -0,0 +1,78 @@
+//! Plays batches of games with a computer player and writes how they went, for balancing
+//! modes and levels without playing them by hand. Needs no window, so it builds without SDL:
+//!
+//!     cargo run --release --no-default-features --bin cats-sim -- --policy beam --seeds 0..1000 --moves 20
+
+extern crate cats;
+
+mod options;
+mod stats;
+
+use std::fs;
+use std::thread;
+
+use cats::level::Level;
+
+use options::{Format, Options};
+use stats::{Run, Summary};
+
+fn main() {
+    let options = Options::from_env().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    });
+    let level = match &options.level {
+        Some(path) => Level::load(path).unwrap_or_else(|error| {
+            eprintln!("{}:{}", path.display(), error);
+            std::process::exit(2);
+        }),
+        None => options.custom_level(),
+    };
+
+    let runs = simulate(&options, &level);
+    let output = match (options.format, options.each) {
+        (Format::Csv, false) => stats::summary_csv(&Summary::new(&runs)),
+        (Format::Json, false) => stats::summary_json(&Summary::new(&runs)),
+        (Format::Csv, true) => stats::runs_csv(&runs),
+        (Format::Json, true) => stats::runs_json(&runs),
+    };
+
+    match &options.output {
+        Some(path) => fs::write(path, output).unwrap_or_else(|error| {
+            eprintln!("{}: {}", path.display(), error);
+            std::process::exit(1);
+        }),
+        None => print!("{}", output),
+    }
+}
+
+/// Plays a game of `level` on every seed, spread over the threads, in seed order.
+fn simulate(options: &Options, level: &Level) -> Vec<Run> {
+    let threads = options.threads
+        .or_else(|| thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1) as u64;
+    let autoplay = options.autoplay();
+
+    let mut runs: Vec<Run> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let autoplay = &autoplay;
+                scope.spawn(move || {
+                    options.seeds.clone()
+                        .filter(|seed| seed % threads == worker)
+                        .map(|seed| {
+                            let mut policy = options.policy.policy(seed);
+                            let playthrough = autoplay.play(&mut level.session(seed), policy.as_mut());
+                            Run { seed, playthrough }
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        workers.into_iter().flat_map(|worker| worker.join().expect("a simulation thread panicked")).collect()
+    });
+
+    runs.sort_by_key(|run| run.seed);
+    runs
+}
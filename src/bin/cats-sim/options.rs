@@ -0,0 +1,236 @@
+use std::env;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cats::ai::{Autoplay, Beam, Greedy, MonteCarlo, Policy};
+use cats::args::{parse_colors, parse_mode, parse_size};
+use cats::engine::{Config, Mode};
+use cats::level::Level;
+
+const USAGE: &str = "usage: cats-sim [--policy greedy|beam|montecarlo] [--seeds <from>..<to>] [--threads <count>]
+                [--level <file> | [--size <width>x<height>] [--colors <count>|<color,...>] [--moves <count> | --time <seconds>] [--loop none|clear|bombs]]
+                [--max-moves <count>] [--move-time <seconds>] [--format csv|json] [--each] [--output <file>]";
+
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum PolicyKind {
+    #[default]
+    Greedy,
+    Beam,
+    MonteCarlo,
+}
+
+impl PolicyKind {
+    /// A policy of this kind, its own random choices drawn from `seed`.
+    pub fn policy(self, seed: u64) -> Box<dyn Policy> {
+        match self {
+            PolicyKind::Greedy => Box::new(Greedy),
+            PolicyKind::Beam => Box::new(Beam::new(4, 2, seed)),
+            PolicyKind::MonteCarlo => Box::new(MonteCarlo::new(4, 8, 4, seed)),
+        }
+    }
+}
+
+impl std::str::FromStr for PolicyKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "greedy" => Ok(PolicyKind::Greedy),
+            "beam" => Ok(PolicyKind::Beam),
+            "montecarlo" => Ok(PolicyKind::MonteCarlo),
+            _ => Err(format!("unknown policy `{}`, expected greedy, beam or montecarlo", name)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum Format {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format `{}`, expected csv or json", name)),
+        }
+    }
+}
+
+/// Command line settings for a batch of games.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub policy: PolicyKind,
+    pub seeds: Range<u64>,
+    pub threads: Option<usize>,
+    pub board: Config,
+    pub mode: Mode,
+    pub level: Option<PathBuf>,
+    pub max_moves: u32,
+    pub move_time: Duration,
+    pub format: Format,
+    /// A row per game instead of the summary.
+    pub each: bool,
+    pub output: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let autoplay = Autoplay::default();
+
+        Options {
+            policy: PolicyKind::default(),
+            seeds: 0..100,
+            threads: None,
+            board: Config::default(),
+            mode: Mode::default(),
+            level: None,
+            max_moves: autoplay.max_moves,
+            move_time: autoplay.move_time,
+            format: Format::default(),
+            each: false,
+            output: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn from_env() -> Result<Self, String> {
+        Options::parse(env::args().skip(1))
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        let mut custom = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--policy" => options.policy = parse_value(&arg, args.next())?,
+                "--seeds" => options.seeds = parse_seeds(&arg, args.next())?,
+                "--threads" => match parse_value(&arg, args.next())? {
+                    0 => return Err(format!("at least one thread is needed\n{}", USAGE)),
+                    threads => options.threads = Some(threads),
+                },
+                "--level" => options.level = Some(parse_value(&arg, args.next())?),
+                "--size" => {
+                    let (width, height) = parse_size(&arg, args.next(), USAGE)?;
+                    options.board.width = width;
+                    options.board.height = height;
+                    custom = true;
+                },
+                "--colors" => {
+                    options.board.colors = parse_colors(&arg, args.next(), USAGE)?;
+                    custom = true;
+                },
+                "--loop" => {
+                    options.board.loop_effect = parse_value(&arg, args.next())?;
+                    custom = true;
+                },
+                "--moves" | "--time" => options.mode = parse_mode(&arg, args.next(), options.mode, USAGE)?,
+                "--max-moves" => options.max_moves = parse_value(&arg, args.next())?,
+                "--move-time" => options.move_time = parse_move_time(&arg, args.next())?,
+                "--format" => options.format = parse_value(&arg, args.next())?,
+                "--each" => options.each = true,
+                "--output" => options.output = Some(parse_value(&arg, args.next())?),
+                _ => return Err(format!("unknown argument `{}`\n{}", arg, USAGE)),
+            }
+        }
+
+        if options.level.is_some() && (custom || options.mode != Mode::Endless) {
+            return Err(format!("a level file sets the board and mode itself\n{}", USAGE));
+        }
+
+        Ok(options)
+    }
+
+    /// The game described by the board and mode flags.
+    pub fn custom_level(&self) -> Level {
+        Level::random(self.board.clone(), self.mode, Vec::new())
+    }
+
+    pub fn autoplay(&self) -> Autoplay {
+        Autoplay { move_time: self.move_time, max_moves: self.max_moves }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    cats::args::parse_value(name, value, USAGE)
+}
+
+/// `from..to`, not including `to`.
+fn parse_seeds(name: &str, value: Option<String>) -> Result<Range<u64>, String> {
+    let value: String = parse_value(name, value)?;
+    let invalid = || format!("invalid value `{}` for `{}`, expected e.g. 0..1000\n{}", value, name, USAGE);
+
+    let mut parts = value.splitn(2, "..");
+    let from: u64 = parts.next().and_then(|part| part.parse().ok()).ok_or_else(invalid)?;
+    let to: u64 = parts.next().and_then(|part| part.parse().ok()).ok_or_else(invalid)?;
+
+    if from >= to {
+        return Err(format!("the seed range `{}` is empty\n{}", value, USAGE));
+    }
+
+    Ok(from..to)
+}
+
+/// Seconds, fractions allowed; negative ones count as none.
+fn parse_move_time(name: &str, value: Option<String>) -> Result<Duration, String> {
+    let value: String = parse_value(name, value)?;
+
+    value.parse::<f64>().ok()
+        .filter(|seconds| !seconds.is_nan())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds.max(0.0)).ok())
+        .ok_or_else(|| format!("invalid value `{}` for `{}`\n{}", value, name, USAGE))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_batches() {
+        assert_eq!(parse(&[]).unwrap(), Options::default());
+
+        let options = parse(&["--policy", "beam", "--seeds", "10..2010", "--format", "json", "--each"]).unwrap();
+        assert_eq!(options.policy, PolicyKind::Beam);
+        assert_eq!(options.seeds, 10..2010);
+        assert_eq!(options.format, Format::Json);
+        assert!(options.each);
+
+        assert!(parse(&["--policy", "random"]).is_err());
+        assert!(parse(&["--seeds", "5..5"]).is_err());
+        assert!(parse(&["--seeds", "5"]).is_err());
+        assert!(parse(&["--threads", "0"]).is_err());
+        assert!(parse(&["--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn parses_games() {
+        let options = parse(&["--size", "6x7", "--colors", "3", "--moves", "20", "--loop", "bombs", "--max-moves", "50"]).unwrap();
+        let level = options.custom_level();
+
+        assert_eq!((level.board.width, level.board.height), (6, 7));
+        assert_eq!(level.board.colors.len(), 3);
+        assert_eq!(level.mode, Mode::Moves(20));
+        assert_eq!(options.autoplay().max_moves, 50);
+
+        assert_eq!(parse(&["--move-time", "0.5"]).unwrap().move_time, Duration::from_millis(500));
+        assert!(parse(&["--move-time", "inf"]).is_err());
+        assert!(parse(&["--move-time", "1e30"]).is_err());
+        assert!(parse(&["--move-time", "NaN"]).is_err());
+        assert!(parse(&["--level", "a.toml", "--colors", "3"]).is_err());
+        assert!(parse(&["--moves", "3", "--time", "3"]).is_err());
+        assert!(parse(&["--colors", "red"]).is_err());
+    }
+}
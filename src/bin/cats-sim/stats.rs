@@ -0,0 +1,234 @@
+use std::fmt::Write;
+
+use cats::ai::Playthrough;
+use cats::engine::Outcome;
+
+/// A game of the batch.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Run {
+    pub seed: u64,
+    pub playthrough: Playthrough,
+}
+
+/// Spread of a value over the games, percentiles by nearest rank.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Distribution {
+    pub min: u64,
+    pub p10: u64,
+    pub p25: u64,
+    pub median: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub deviation: f64,
+}
+
+impl Distribution {
+    pub fn new(values: &[u64]) -> Self {
+        if values.is_empty() {
+            return Distribution::default();
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+
+        let count = sorted.len() as f64;
+        let rank = |percent: usize| sorted[(percent * sorted.len()).div_ceil(100).max(1) - 1];
+        let mean = sorted.iter().map(|&value| value as f64).sum::<f64>() / count;
+        let variance = sorted.iter().map(|&value| (value as f64 - mean).powi(2)).sum::<f64>() / count;
+
+        Distribution {
+            min: sorted[0],
+            p10: rank(10),
+            p25: rank(25),
+            median: rank(50),
+            p75: rank(75),
+            p90: rank(90),
+            max: sorted[sorted.len() - 1],
+            mean,
+            deviation: variance.sqrt(),
+        }
+    }
+}
+
+/// What a batch of games adds up to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Summary {
+    pub games: usize,
+    pub score: Distribution,
+    pub moves: f64,
+    /// Share of the games that ended on a board without any move.
+    pub stuck: f64,
+    /// Share of the turns that closed a loop.
+    pub loops: f64,
+    /// Shuffles per game.
+    pub shuffles: f64,
+    /// Share of the games that were won, for levels with goals.
+    pub won: f64,
+}
+
+impl Summary {
+    pub fn new(runs: &[Run]) -> Self {
+        let games = runs.len().max(1) as f64;
+        let sum = |value: &dyn Fn(&Playthrough) -> u64| runs.iter().map(|run| value(&run.playthrough)).sum::<u64>() as f64;
+        let turns = sum(&|playthrough| u64::from(playthrough.moves));
+
+        Summary {
+            games: runs.len(),
+            score: Distribution::new(&runs.iter().map(|run| run.playthrough.score).collect::<Vec<_>>()),
+            moves: turns / games,
+            stuck: sum(&|playthrough| u64::from(playthrough.stuck)) / games,
+            loops: if turns > 0.0 { sum(&|playthrough| u64::from(playthrough.loops)) / turns } else { 0.0 },
+            shuffles: sum(&|playthrough| u64::from(playthrough.shuffles)) / games,
+            won: sum(&|playthrough| u64::from(playthrough.outcome == Outcome::Won)) / games,
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let score = &self.score;
+
+        vec![
+            ("games", self.games.to_string()),
+            ("score_min", score.min.to_string()),
+            ("score_p10", score.p10.to_string()),
+            ("score_p25", score.p25.to_string()),
+            ("score_median", score.median.to_string()),
+            ("score_p75", score.p75.to_string()),
+            ("score_p90", score.p90.to_string()),
+            ("score_max", score.max.to_string()),
+            ("score_mean", decimal(score.mean)),
+            ("score_deviation", decimal(score.deviation)),
+            ("moves_mean", decimal(self.moves)),
+            ("stuck_rate", decimal(self.stuck)),
+            ("loop_rate", decimal(self.loops)),
+            ("shuffles_mean", decimal(self.shuffles)),
+            ("win_rate", decimal(self.won)),
+        ]
+    }
+}
+
+fn run_fields(run: &Run) -> Vec<(&'static str, String)> {
+    let playthrough = &run.playthrough;
+    let outcome = match playthrough.outcome {
+        Outcome::Playing => "playing",
+        Outcome::Won => "won",
+        Outcome::Lost => "lost",
+        Outcome::Finished => "finished",
+    };
+
+    vec![
+        ("seed", run.seed.to_string()),
+        ("score", playthrough.score.to_string()),
+        ("moves", playthrough.moves.to_string()),
+        ("loops", playthrough.loops.to_string()),
+        ("shuffles", playthrough.shuffles.to_string()),
+        ("stuck", playthrough.stuck.to_string()),
+        ("outcome", format!("\"{}\"", outcome)),
+        ("seconds", decimal(playthrough.elapsed.as_secs_f64())),
+    ]
+}
+
+fn decimal(value: f64) -> String {
+    format!("{:.4}", value)
+}
+
+/// A header line and a line per row, values that are strings in quotes.
+fn csv(rows: &[Vec<(&'static str, String)>]) -> String {
+    let mut text = String::new();
+
+    if let Some(first) = rows.first() {
+        let names: Vec<&str> = first.iter().map(|(name, _)| *name).collect();
+        writeln!(text, "{}", names.join(",")).unwrap();
+    }
+
+    rows.iter().for_each(|row| {
+        let values: Vec<&str> = row.iter().map(|(_, value)| value.as_str()).collect();
+        writeln!(text, "{}", values.join(",")).unwrap();
+    });
+
+    text
+}
+
+fn json_object(row: &[(&'static str, String)]) -> String {
+    let fields: Vec<String> = row.iter().map(|(name, value)| format!("\"{}\": {}", name, value)).collect();
+
+    format!("{{{}}}", fields.join(", "))
+}
+
+pub fn summary_csv(summary: &Summary) -> String {
+    csv(&[summary.fields()])
+}
+
+pub fn summary_json(summary: &Summary) -> String {
+    format!("{}\n", json_object(&summary.fields()))
+}
+
+pub fn runs_csv(runs: &[Run]) -> String {
+    csv(&runs.iter().map(run_fields).collect::<Vec<_>>())
+}
+
+/// A JSON array with an object per game, one per line.
+pub fn runs_json(runs: &[Run]) -> String {
+    let objects: Vec<String> = runs.iter().map(|run| format!("  {}", json_object(&run_fields(run)))).collect();
+
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn run(seed: u64, score: u64, loops: u32, stuck: bool) -> Run {
+        Run {
+            seed,
+            playthrough: Playthrough {
+                score,
+                moves: 10,
+                loops,
+                shuffles: 1,
+                stuck,
+                outcome: Outcome::Finished,
+                elapsed: Duration::from_secs(20),
+            },
+        }
+    }
+
+    #[test]
+    fn distributions_use_nearest_rank() {
+        let values: Vec<u64> = (1..=20).collect();
+        let distribution = Distribution::new(&values);
+
+        assert_eq!((distribution.min, distribution.p10, distribution.p25), (1, 2, 5));
+        assert_eq!((distribution.median, distribution.p75, distribution.p90, distribution.max), (10, 15, 18, 20));
+        assert_eq!(distribution.mean, 10.5);
+        assert_eq!(Distribution::new(&[7]).p10, 7);
+        assert_eq!(Distribution::new(&[]), Distribution::default());
+    }
+
+    #[test]
+    fn summaries_count_rates() {
+        let summary = Summary::new(&[run(0, 10, 2, false), run(1, 30, 0, true)]);
+
+        assert_eq!(summary.games, 2);
+        assert_eq!(summary.score.mean, 20.0);
+        assert_eq!(summary.moves, 10.0);
+        assert_eq!(summary.stuck, 0.5);
+        assert_eq!(summary.loops, 0.1);
+        assert_eq!(summary.shuffles, 1.0);
+        assert_eq!(summary.won, 0.0);
+    }
+
+    #[test]
+    fn writes_csv_and_json() {
+        let runs = [run(4, 12, 1, false), run(5, 3, 0, true)];
+
+        assert_eq!(runs_csv(&runs), "seed,score,moves,loops,shuffles,stuck,outcome,seconds\n\
+            4,12,10,1,1,false,\"finished\",20.0000\n\
+            5,3,10,0,1,true,\"finished\",20.0000\n");
+        assert!(runs_json(&runs).starts_with("[\n  {\"seed\": 4, \"score\": 12,"));
+        assert!(summary_json(&Summary::new(&runs)).contains("\"stuck_rate\": 0.5000"));
+        assert_eq!(summary_csv(&Summary::new(&runs)).lines().count(), 2);
+    }
+}
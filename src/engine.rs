@@ -0,0 +1,11 @@
+pub mod board;
+pub mod color;
+pub mod goal;
+pub mod score;
+pub mod session;
+
+pub use board::{Board, Cell, Chain, Clear, Config, Fall, Layout, LoopEffect, Step, Tile};
+pub use color::Color;
+pub use goal::{Goal, Outcome};
+pub use score::{Bonus, Breakdown, Part, Play, Rules, Scoring};
+pub use session::{Hint, Mode, Progress, Session, Turn};
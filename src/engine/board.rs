@@ -0,0 +1,1062 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_pcg::Pcg32;
+
+use crate::engine::color::Color;
+
+pub const MIN_SIZE: usize = 2;
+pub const MAX_SIZE: usize = 16;
+
+/// Reaches one cell past the loop around a bomb.
+pub const BLAST_RADIUS: usize = 2;
+/// Tries at finding a board with a move before giving up on it; only boards with hardly
+/// any dots of a color need more than a few.
+const SHUFFLE_ATTEMPTS: usize = 100;
+
+/// What a closed chain does to the dots it encloses.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum LoopEffect {
+    /// Enclosed dots stay.
+    #[default]
+    None,
+    /// Enclosed dots are cleared along with the loop.
+    Clear,
+    /// Enclosed dots turn into bombs that clear every dot within `BLAST_RADIUS` of them.
+    Bombs,
+}
+
+impl LoopEffect {
+    pub fn name(self) -> &'static str {
+        match self {
+            LoopEffect::None => "none",
+            LoopEffect::Clear => "clear",
+            LoopEffect::Bombs => "bombs",
+        }
+    }
+}
+
+impl fmt::Display for LoopEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LoopEffect {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [LoopEffect::None, LoopEffect::Clear, LoopEffect::Bombs].iter()
+            .find(|effect| effect.name() == name)
+            .copied()
+            .ok_or_else(|| format!("unknown loop effect `{}`, expected none, clear or bombs", name))
+    }
+}
+
+/// Shape of a board, the colors its dots are drawn from and what loops do.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Config {
+    pub width: usize,
+    pub height: usize,
+    pub colors: Vec<Color>,
+    pub loop_effect: LoopEffect,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 5,
+            height: 5,
+            colors: Color::DEFAULT.to_vec(),
+            loop_effect: LoopEffect::None,
+        }
+    }
+}
+
+/// Position of a dot on the board, `x` is the column and `y` is the row counting from the top.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub struct Cell {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Cell {
+    pub fn new(x: usize, y: usize) -> Self {
+        Cell { x, y }
+    }
+
+    pub fn is_sibling(self, other: Cell) -> bool {
+        let dx = (self.x as i32 - other.x as i32).abs();
+        let dy = (self.y as i32 - other.y as i32).abs();
+
+        dx + dy == 1
+    }
+}
+
+/// What extending the chain towards a cell did.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Step {
+    Connect,
+    Disconnect,
+    None,
+}
+
+/// Path the player is currently drawing.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Chain {
+    cells: Vec<Cell>,
+    closed: bool,
+    color: Option<Color>,
+}
+
+impl Chain {
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn last(&self) -> Option<Cell> {
+        self.cells.last().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    fn is_previous(&self, cell: Cell) -> bool {
+        self.cells.len() >= 2 && self.cells[self.cells.len() - 2] == cell
+    }
+}
+
+/// A dot moving to another cell: sliding down into the gap left by cleared dots, or shuffled.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Fall {
+    pub from: Cell,
+    pub to: Cell,
+}
+
+/// Outcome of a committed chain.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Clear {
+    pub color: Color,
+    pub closed: bool,
+    /// The chain as it was drawn, a loop ends on the cell it was closed on.
+    pub chain: Vec<Cell>,
+    /// Every cleared dot: the chain, the rest of its color for a loop and whatever the loop effect took.
+    pub cleared: Vec<Cell>,
+    /// Dots inside a loop.
+    pub enclosed: Vec<Cell>,
+    /// Bombs that went off, each clears the dots around it.
+    pub exploded: Vec<Cell>,
+    /// How many dots of each color were cleared, in palette order.
+    pub counts: Vec<(Color, usize)>,
+    pub falls: Vec<Fall>,
+    pub spawned: Vec<Cell>,
+    /// Where the dots went when the refilled board had no move left and was shuffled,
+    /// from their cells after the falls.
+    pub shuffled: Vec<Fall>,
+}
+
+impl Clear {
+    pub fn count(&self, color: Color) -> usize {
+        self.counts.iter().find(|(counted, _)| *counted == color).map_or(0, |(_, count)| *count)
+    }
+}
+
+/// Starting content of a cell.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Tile {
+    /// A dot of a random color.
+    Random,
+    Dot(Color),
+    /// An obstacle: never holds a dot, dots above it fall past it.
+    Wall,
+}
+
+/// Starting tiles indexed by column, then row.
+pub type Layout = Vec<Vec<Tile>>;
+
+/// Random numbers for new dots. Counts how far it is into its sequence, so a saved game can
+/// pick up the same sequence from the seed.
+#[derive(Debug, Clone)]
+struct Generator {
+    seed: u64,
+    /// 32-bit numbers drawn so far.
+    draws: u64,
+    rng: Pcg32,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        Generator { seed, draws: 0, rng: Pcg32::seed_from_u64(seed) }
+    }
+
+    /// The generator of `seed` after `draws` numbers.
+    fn resume(seed: u64, draws: u64) -> Self {
+        let mut generator = Generator::new(seed);
+        (0..draws).for_each(|_| { generator.next_u32(); });
+        generator
+    }
+}
+
+impl RngCore for Generator {
+    fn next_u32(&mut self) -> u32 {
+        self.draws += 1;
+        self.rng.next_u32()
+    }
+
+    /// Two 32-bit draws, low half first, the same way `Pcg32` makes its 64-bit numbers.
+    fn next_u64(&mut self) -> u64 {
+        let low = u64::from(self.next_u32());
+        let high = u64::from(self.next_u32());
+        (high << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.chunks_mut(4).for_each(|chunk| {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        });
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// The grid of dots. Every dot, including the ones dropped in after a clear, comes from
+/// the board's own generator, so a seed and a sequence of moves always replay the same game.
+#[derive(Debug, Clone)]
+pub struct Board {
+    /// Dots by column, then row. Walls are `None`.
+    field: Vec<Vec<Option<Color>>>,
+    chain: Chain,
+    colors: Vec<Color>,
+    loop_effect: LoopEffect,
+    rng: Generator,
+}
+
+impl Board {
+    pub fn new(config: &Config, seed: u64) -> Self {
+        let layout = vec![vec![Tile::Random; config.height]; config.width];
+
+        Board::with_layout(config, &layout, seed)
+    }
+
+    /// Board with some of the dots and walls placed up front, the rest is random.
+    /// The layout decides the size of the board, `config` the rest.
+    pub fn with_layout(config: &Config, layout: &[Vec<Tile>], seed: u64) -> Self {
+        let colors = &config.colors;
+        assert!(!colors.is_empty(), "a board needs at least one color");
+
+        let mut rng = Generator::new(seed);
+        let field = layout.iter()
+            .map(|column| column.iter().map(|tile| match tile {
+                Tile::Random => Some(Board::random_color(colors, &mut rng)),
+                Tile::Dot(color) => Some(*color),
+                Tile::Wall => None,
+            }).collect())
+            .collect();
+
+        let mut board = Board {
+            field,
+            chain: Chain::default(),
+            colors: colors.to_vec(),
+            loop_effect: config.loop_effect,
+            rng,
+        };
+
+        // Random dots are drawn again until there is a move; a board the layout leaves
+        // without one is shuffled like any other.
+        let random: Vec<Cell> = board.cells().filter(|cell| layout[cell.x][cell.y] == Tile::Random).collect();
+        for _ in 0..SHUFFLE_ATTEMPTS {
+            if random.is_empty() || board.has_move() {
+                break;
+            }
+
+            random.iter().for_each(|cell| board.field[cell.x][cell.y] = Some(Board::random_color(colors, &mut board.rng)));
+        }
+        board.shuffle();
+
+        board
+    }
+
+    /// Moves the generator on to where it was after `draws` numbers, for picking up a saved game
+    /// whose dots are all placed by the layout.
+    pub fn with_draws(mut self, draws: u64) -> Self {
+        self.rng = Generator::resume(self.rng.seed, draws);
+        self
+    }
+
+    /// A copy that draws the dots to come from `seed` instead, for trying moves out without
+    /// knowing which dots will really fall in.
+    pub fn reseeded(&self, seed: u64) -> Self {
+        Board { rng: Generator::new(seed), ..self.clone() }
+    }
+
+    pub fn width(&self) -> usize {
+        self.field.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.field.first().map_or(0, Vec::len)
+    }
+
+    /// The seed the board was created with.
+    pub fn seed(&self) -> u64 {
+        self.rng.seed
+    }
+
+    /// Random numbers used so far, see `with_draws`.
+    pub fn draws(&self) -> u64 {
+        self.rng.draws
+    }
+
+    pub fn loop_effect(&self) -> LoopEffect {
+        self.loop_effect
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Cell> {
+        let height = self.height();
+
+        (0..self.width()).flat_map(move |x| (0..height).map(move |y| Cell::new(x, y)))
+    }
+
+    /// Color of the dot in the cell, `None` for walls and cells off the board.
+    pub fn color(&self, cell: Cell) -> Option<Color> {
+        self.field.get(cell.x).and_then(|column| column.get(cell.y)).copied().flatten()
+    }
+
+    pub fn is_wall(&self, cell: Cell) -> bool {
+        self.field.get(cell.x).and_then(|column| column.get(cell.y)) == Some(&None)
+    }
+
+    /// Colors new dots are picked from.
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Whether two neighbouring dots share a color, so there is a chain to draw.
+    pub fn has_move(&self) -> bool {
+        self.cells().any(|cell| {
+            let color = self.color(cell);
+            color.is_some() && [Cell::new(cell.x + 1, cell.y), Cell::new(cell.x, cell.y + 1)].iter().any(|&next| self.color(next) == color)
+        })
+    }
+
+    /// Chains that can be drawn on the board: open ones of two dots or more, drawn from their
+    /// top left end, and loops. Searches depth first from every dot in turn, giving each the same
+    /// share of `limit` paths and at least one, so large boards are covered everywhere but not
+    /// exhaustively.
+    pub fn chains(&self, limit: usize) -> Vec<Chain> {
+        let dots: Vec<Cell> = self.cells().filter(|&cell| self.color(cell).is_some()).collect();
+        let budget = (limit / dots.len().max(1)).max(1);
+        let mut chains = Vec::new();
+
+        for &start in &dots {
+            let mut stack = vec![Chain { cells: vec![start], closed: false, color: self.color(start) }];
+            let mut searched = 0;
+
+            while let Some(chain) = stack.pop() {
+                if searched == budget {
+                    break;
+                }
+
+                let last = chain.cells[chain.len() - 1];
+                if chain.len() >= 2 {
+                    searched += 1;
+                }
+                if chain.closed || (chain.len() >= 2 && (start.y, start.x) < (last.y, last.x)) {
+                    chains.push(chain.clone());
+                }
+
+                if chain.closed {
+                    continue;
+                }
+
+                let siblings = [(1, 0), (0, 1), (-1, 0), (0, -1)].iter()
+                    .filter_map(|&(dx, dy)| Some(Cell::new(last.x.checked_add_signed(dx)?, last.y.checked_add_signed(dy)?)))
+                    .filter(|&next| self.color(next) == chain.color && !chain.is_previous(next));
+
+                siblings.for_each(|next| {
+                    let mut longer = chain.clone();
+                    longer.closed = chain.cells.contains(&next);
+                    longer.cells.push(next);
+                    stack.push(longer);
+                });
+            }
+        }
+
+        chains
+    }
+
+    /// Whether the dot is part of the chain, a closed chain takes in every dot of its color.
+    pub fn is_connected(&self, cell: Cell) -> bool {
+        if self.chain.closed && self.color(cell) == self.chain.color {
+            return true;
+        }
+
+        self.chain.cells.contains(&cell)
+    }
+
+    pub fn start_chain(&mut self, cell: Cell) -> bool {
+        let color = match self.color(cell) {
+            Some(color) => color,
+            None => return false,
+        };
+
+        self.chain = Chain {
+            cells: vec![cell],
+            closed: false,
+            color: Some(color),
+        };
+
+        true
+    }
+
+    pub fn extend_chain(&mut self, cell: Cell) -> Step {
+        let step = self.resolve_step(cell);
+
+        match step {
+            Step::Connect => self.connect(cell),
+            Step::Disconnect => self.disconnect(),
+            Step::None => (),
+        }
+
+        step
+    }
+
+    /// Takes the last dot off the chain, down to the one it started from.
+    pub fn retract_chain(&mut self) -> bool {
+        let retracted = self.chain.len() >= 2;
+        self.disconnect();
+
+        retracted
+    }
+
+    /// Drops the chain being drawn without clearing anything. Returns whether there was one.
+    pub fn cancel_chain(&mut self) -> bool {
+        let drawing = !self.chain.is_empty();
+        self.reset_chain();
+
+        drawing
+    }
+
+    /// Clears the connected dots and refills the board. Chains shorter than two dots are dropped.
+    pub fn commit_chain(&mut self) -> Option<Clear> {
+        if self.chain.len() < 2 {
+            self.reset_chain();
+            return None;
+        }
+
+        let mut clear = self.clear_points();
+
+        self.reset_chain();
+        clear.shuffled = self.shuffle();
+
+        Some(clear)
+    }
+
+    /// Mixes the dots up, keeping how many there are of each color, until two neighbours share
+    /// a color. Boards that have a move, or never find one, are left as they are.
+    /// Returns where each dot went.
+    fn shuffle(&mut self) -> Vec<Fall> {
+        if self.has_move() {
+            return Vec::new();
+        }
+
+        let cells: Vec<Cell> = self.cells().filter(|&cell| self.color(cell).is_some()).collect();
+        let mut order: Vec<usize> = (0..cells.len()).collect();
+        let original = self.field.clone();
+
+        for _ in 0..SHUFFLE_ATTEMPTS {
+            order.shuffle(&mut self.rng);
+            cells.iter().zip(&order).for_each(|(to, &from)| {
+                self.field[to.x][to.y] = original[cells[from].x][cells[from].y];
+            });
+
+            if self.has_move() {
+                return cells.iter().zip(&order)
+                    .filter(|(to, &from)| cells[from] != **to)
+                    .map(|(&to, &from)| Fall { from: cells[from], to })
+                    .collect();
+            }
+        }
+
+        self.field = original;
+        Vec::new()
+    }
+
+    pub(crate) fn reset_chain(&mut self) {
+        self.chain = Chain::default();
+    }
+
+    fn resolve_step(&self, next: Cell) -> Step {
+        let prev = match self.chain.last() {
+            Some(prev) => prev,
+            None => return Step::None,
+        };
+
+        if prev == next || self.color(next).is_none() || self.color(prev) != self.color(next) {
+            return Step::None;
+        }
+
+        if !prev.is_sibling(next) {
+            return Step::None;
+        }
+
+        if self.chain.is_previous(next) {
+            Step::Disconnect
+        } else {
+            Step::Connect
+        }
+    }
+
+    fn connect(&mut self, next: Cell) {
+        if self.chain.closed {
+            return;
+        }
+
+        if self.chain.cells.contains(&next) {
+            self.chain.closed = true;
+        }
+
+        self.chain.cells.push(next);
+    }
+
+    fn disconnect(&mut self) {
+        if self.chain.len() < 2 {
+            return;
+        }
+
+        self.chain.cells.pop();
+        self.chain.closed = false;
+    }
+
+    /// Dots inside the closed chain: the ones that can't reach the edge of the board
+    /// without crossing the chain.
+    fn enclosed(&self) -> Vec<Cell> {
+        if !self.chain.closed {
+            return Vec::new();
+        }
+
+        let mut outside: Vec<Cell> = self.cells()
+            .filter(|cell| cell.x == 0 || cell.y == 0 || cell.x + 1 == self.width() || cell.y + 1 == self.height())
+            .filter(|cell| !self.chain.cells.contains(cell))
+            .collect();
+        let mut queue = outside.clone();
+
+        while let Some(cell) = queue.pop() {
+            self.cells()
+                .filter(|next| next.is_sibling(cell))
+                .filter(|next| !self.chain.cells.contains(next) && !outside.contains(next))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .for_each(|next| {
+                    outside.push(next);
+                    queue.push(next);
+                });
+        }
+
+        self.cells()
+            .filter(|cell| self.color(*cell).is_some())
+            .filter(|cell| !self.chain.cells.contains(cell) && !outside.contains(cell))
+            .collect()
+    }
+
+    fn clear_points(&mut self) -> Clear {
+        let enclosed = self.enclosed();
+        let exploded = match self.loop_effect {
+            LoopEffect::Bombs => enclosed.clone(),
+            _ => Vec::new(),
+        };
+        let cleared: Vec<Cell> = self.cells()
+            .filter(|&cell| self.color(cell).is_some())
+            .filter(|&cell| {
+                self.is_connected(cell)
+                    || (self.loop_effect == LoopEffect::Clear && enclosed.contains(&cell))
+                    || exploded.iter().any(|bomb| bomb.x.abs_diff(cell.x) <= BLAST_RADIUS && bomb.y.abs_diff(cell.y) <= BLAST_RADIUS)
+            })
+            .collect();
+        let counts = self.colors.iter()
+            .map(|&color| (color, cleared.iter().filter(|&&cell| self.color(cell) == Some(color)).count()))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        let mut falls = Vec::new();
+        let mut spawned = Vec::new();
+
+        for x in 0..self.width() {
+            let slots: Vec<usize> = (0..self.height()).filter(|&y| self.field[x][y].is_some()).collect();
+            let survivors: Vec<(usize, Option<Color>)> = slots.iter()
+                .filter(|&&y| !cleared.contains(&Cell::new(x, y)))
+                .map(|&y| (y, self.field[x][y]))
+                .collect();
+            let gap = slots.len() - survivors.len();
+
+            for &y in &slots[..gap] {
+                self.field[x][y] = Some(Board::random_color(&self.colors, &mut self.rng));
+                spawned.push(Cell::new(x, y));
+            }
+
+            for (&y, (from, color)) in slots[gap..].iter().zip(survivors) {
+                if from != y {
+                    falls.push(Fall { from: Cell::new(x, from), to: Cell::new(x, y) });
+                }
+                self.field[x][y] = color;
+            }
+        }
+
+        Clear {
+            color: self.chain.color.unwrap(),
+            closed: self.chain.closed,
+            chain: self.chain.cells.clone(),
+            cleared,
+            enclosed,
+            exploded,
+            counts,
+            falls,
+            spawned,
+            shuffled: Vec::new(),
+        }
+    }
+
+    fn random_color(colors: &[Color], rng: &mut Generator) -> Color {
+        colors[rng.gen_range(0, colors.len())]
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Builds a board from rows of color letters: `P`urple, `B`lue, `G`reen, `R`ed, `C`yan, `#` for walls.
+    pub(crate) fn board_from_rows(rows: &[&str]) -> Board {
+        let height = rows.len();
+        let width = rows[0].len();
+
+        let field = (0..width)
+            .map(|x| {
+                (0..height)
+                    .map(|y| match rows[y].as_bytes()[x] {
+                        b'P' => Some(Color::Purple),
+                        b'B' => Some(Color::Blue),
+                        b'G' => Some(Color::Green),
+                        b'R' => Some(Color::Red),
+                        b'C' => Some(Color::Cyan),
+                        b'#' => None,
+                        symbol => panic!("unknown color {}", symbol as char),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Board {
+            field,
+            chain: Chain::default(),
+            colors: Color::DEFAULT.to_vec(),
+            loop_effect: LoopEffect::None,
+            rng: Generator::new(0),
+        }
+    }
+
+    fn colors(board: &Board) -> Vec<Option<Color>> {
+        board.cells().map(|cell| board.color(cell)).collect()
+    }
+
+    #[test]
+    fn same_seed_same_board() {
+        let config = Config::default();
+
+        assert_eq!(colors(&Board::new(&config, 42)), colors(&Board::new(&config, 42)));
+        assert_ne!(colors(&Board::new(&config, 42)), colors(&Board::new(&config, 43)));
+    }
+
+    #[test]
+    fn resumed_board_continues_the_sequence() {
+        let mut played = Board::new(&Config::default(), 11);
+        played.rng.next_u64();
+
+        let layout: Layout = (0..played.width())
+            .map(|x| (0..played.height()).map(|y| Tile::Dot(played.color(Cell::new(x, y)).unwrap())).collect())
+            .collect();
+        let mut resumed = Board::with_layout(&Config::default(), &layout, 11).with_draws(played.draws());
+
+        assert_eq!(resumed.draws(), played.draws());
+        assert_eq!(colors(&resumed), colors(&played));
+
+        for board in [&mut played, &mut resumed].iter_mut() {
+            let color = board.color(Cell::new(0, 0));
+            board.field[0][1] = color;
+            board.start_chain(Cell::new(0, 0));
+            board.extend_chain(Cell::new(0, 1));
+            board.commit_chain().unwrap();
+        }
+
+        assert_eq!(colors(&resumed), colors(&played));
+        assert_eq!(resumed.rng.next_u32(), played.rng.next_u32());
+    }
+
+    #[test]
+    fn board_has_configured_size() {
+        let board = Board::new(&Config { width: 7, height: 9, ..Config::default() }, 1);
+
+        assert_eq!(board.width(), 7);
+        assert_eq!(board.height(), 9);
+        assert_eq!(board.cells().count(), 63);
+        assert!(board.color(Cell::new(6, 8)).is_some());
+        assert_eq!(board.color(Cell::new(7, 0)), None);
+        assert_eq!(board.color(Cell::new(0, 9)), None);
+    }
+
+    #[test]
+    fn dots_come_from_configured_colors() {
+        let colors = vec![Color::Red, Color::Orange, Color::Gray];
+        let config = Config { width: 8, height: 8, colors: colors.clone(), ..Config::default() };
+        let mut board = Board::new(&config, 3);
+
+        assert!(board.cells().all(|cell| colors.contains(&board.color(cell).unwrap())));
+
+        for _ in 0..10 {
+            board.start_chain(Cell::new(0, 0));
+            board.chain.cells.push(Cell::new(0, 1));
+            board.commit_chain();
+        }
+
+        assert!(board.cells().all(|cell| colors.contains(&board.color(cell).unwrap())));
+        assert!(colors.iter().all(|color| board.cells().any(|cell| board.color(cell) == Some(*color))));
+    }
+
+    #[test]
+    fn layout_places_dots_and_walls() {
+        let layout = vec![
+            vec![Tile::Dot(Color::Red), Tile::Wall],
+            vec![Tile::Random, Tile::Dot(Color::Gray)],
+        ];
+        let config = Config { colors: vec![Color::Blue], ..Config::default() };
+        let mut board = Board::with_layout(&config, &layout, 0);
+
+        assert_eq!(board.color(Cell::new(0, 0)), Some(Color::Red));
+        assert_eq!(board.color(Cell::new(0, 1)), None);
+        assert!(board.is_wall(Cell::new(0, 1)));
+        assert!(!board.is_wall(Cell::new(0, 0)));
+        assert!(!board.is_wall(Cell::new(5, 5)));
+        assert_eq!(board.color(Cell::new(1, 0)), Some(Color::Blue));
+        assert_eq!(board.color(Cell::new(1, 1)), Some(Color::Gray));
+
+        assert!(!board.start_chain(Cell::new(0, 1)));
+        assert!(board.start_chain(Cell::new(0, 0)));
+        assert_eq!(board.extend_chain(Cell::new(0, 1)), Step::None);
+    }
+
+    fn close_loop(board: &mut Board) {
+        board.start_chain(Cell::new(0, 0));
+        [(1, 0), (2, 0), (2, 1), (2, 2), (1, 2), (0, 2), (0, 1), (0, 0)].iter()
+            .for_each(|&(x, y)| assert_eq!(board.extend_chain(Cell::new(x, y)), Step::Connect));
+    }
+
+    #[test]
+    fn loop_leaves_enclosed_dots_by_default() {
+        let mut board = board_from_rows(&["RRRG", "RBRG", "RRRG", "GGGG"]);
+
+        close_loop(&mut board);
+        let clear = board.commit_chain().unwrap();
+
+        assert!(clear.closed);
+        assert_eq!(clear.enclosed, vec![Cell::new(1, 1)]);
+        assert!(clear.exploded.is_empty());
+        assert_eq!(clear.counts, vec![(Color::Red, 8)]);
+    }
+
+    #[test]
+    fn loop_clears_enclosed_dots() {
+        let mut board = board_from_rows(&["RRRG", "RBRG", "RRRG", "GGGG"]);
+        board.loop_effect = LoopEffect::Clear;
+
+        close_loop(&mut board);
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.count(Color::Blue), 1);
+        assert_eq!(clear.count(Color::Green), 0);
+        assert_eq!(clear.cleared.len(), 9);
+    }
+
+    #[test]
+    fn loop_turns_enclosed_dots_into_bombs() {
+        let mut board = board_from_rows(&["RRRG", "RBRG", "RRRG", "GGGG"]);
+        board.loop_effect = LoopEffect::Bombs;
+
+        close_loop(&mut board);
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.exploded, vec![Cell::new(1, 1)]);
+        assert_eq!(clear.count(Color::Blue), 1);
+        assert_eq!(clear.count(Color::Green), 7);
+
+        let mut board = board_from_rows(&["RRRRG", "RBBRG", "RRRRG", "GGGGG"]);
+        board.loop_effect = LoopEffect::Bombs;
+
+        board.start_chain(Cell::new(0, 0));
+        [(1, 0), (2, 0), (3, 0), (3, 1), (3, 2), (2, 2), (1, 2), (0, 2), (0, 1), (0, 0)].iter()
+            .for_each(|&(x, y)| { board.extend_chain(Cell::new(x, y)); });
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.exploded, vec![Cell::new(1, 1), Cell::new(2, 1)]);
+        assert_eq!(clear.count(Color::Green), 5 + 3);
+    }
+
+    #[test]
+    fn loop_effect_names() {
+        assert_eq!("bombs".parse(), Ok(LoopEffect::Bombs));
+        assert_eq!(LoopEffect::Clear.to_string(), "clear");
+        assert!("boom".parse::<LoopEffect>().is_err());
+    }
+
+    #[test]
+    fn dots_fall_past_walls() {
+        let mut board = board_from_rows(&["RG", "#G", "BB"]);
+
+        board.start_chain(Cell::new(0, 2));
+        board.extend_chain(Cell::new(1, 2));
+
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.falls, vec![
+            Fall { from: Cell::new(0, 0), to: Cell::new(0, 2) },
+            Fall { from: Cell::new(1, 0), to: Cell::new(1, 1) },
+            Fall { from: Cell::new(1, 1), to: Cell::new(1, 2) },
+        ]);
+        assert_eq!(clear.spawned, vec![Cell::new(0, 0), Cell::new(1, 0)]);
+        assert!(board.is_wall(Cell::new(0, 1)));
+        assert_eq!(board.color(Cell::new(0, 2)), Some(Color::Red));
+        assert_eq!(board.color(Cell::new(1, 2)), Some(Color::Green));
+    }
+
+    #[test]
+    fn refill_keeps_non_square_board_shape() {
+        let mut board = board_from_rows(&["RGB", "RGR", "GBB", "GBB"]);
+
+        board.start_chain(Cell::new(2, 3));
+        board.extend_chain(Cell::new(2, 2));
+        board.extend_chain(Cell::new(1, 2));
+        board.extend_chain(Cell::new(1, 3));
+
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.cleared.len(), 4);
+        assert_eq!(board.width(), 3);
+        assert_eq!(board.height(), 4);
+        assert_eq!(clear.falls, vec![
+            Fall { from: Cell::new(1, 0), to: Cell::new(1, 2) },
+            Fall { from: Cell::new(1, 1), to: Cell::new(1, 3) },
+            Fall { from: Cell::new(2, 0), to: Cell::new(2, 2) },
+            Fall { from: Cell::new(2, 1), to: Cell::new(2, 3) },
+        ]);
+        assert_eq!(board.color(Cell::new(1, 3)), Some(Color::Green));
+        assert_eq!(board.color(Cell::new(2, 3)), Some(Color::Red));
+    }
+
+    #[test]
+    fn chain_connects_siblings_of_same_color() {
+        let mut board = board_from_rows(&["RRB", "GRB", "GGB"]);
+
+        assert!(board.start_chain(Cell::new(0, 0)));
+        assert_eq!(board.extend_chain(Cell::new(1, 0)), Step::Connect);
+        assert_eq!(board.extend_chain(Cell::new(2, 0)), Step::None);
+        assert_eq!(board.extend_chain(Cell::new(1, 2)), Step::None);
+        assert_eq!(board.extend_chain(Cell::new(1, 1)), Step::Connect);
+
+        assert_eq!(board.chain().cells(), &[Cell::new(0, 0), Cell::new(1, 0), Cell::new(1, 1)]);
+        assert!(board.is_connected(Cell::new(1, 0)));
+        assert!(!board.is_connected(Cell::new(2, 0)));
+    }
+
+    #[test]
+    fn chain_disconnects_when_moving_back() {
+        let mut board = board_from_rows(&["RRR", "GGG", "BBB"]);
+
+        board.start_chain(Cell::new(0, 0));
+        board.extend_chain(Cell::new(1, 0));
+        board.extend_chain(Cell::new(2, 0));
+
+        assert_eq!(board.extend_chain(Cell::new(1, 0)), Step::Disconnect);
+        assert_eq!(board.chain().cells(), &[Cell::new(0, 0), Cell::new(1, 0)]);
+        assert!(!board.is_connected(Cell::new(2, 0)));
+    }
+
+    #[test]
+    fn closed_chain_connects_every_dot_of_color() {
+        let mut board = board_from_rows(&["RRG", "RRG", "GGR"]);
+
+        board.start_chain(Cell::new(0, 0));
+        board.extend_chain(Cell::new(1, 0));
+        board.extend_chain(Cell::new(1, 1));
+        board.extend_chain(Cell::new(0, 1));
+        board.extend_chain(Cell::new(0, 0));
+
+        assert!(board.chain().is_closed());
+        assert!(board.is_connected(Cell::new(2, 2)));
+
+        let mut closed = board.clone();
+        assert_eq!(closed.commit_chain().unwrap().count(Color::Red), 5);
+
+        assert_eq!(board.extend_chain(Cell::new(0, 1)), Step::Disconnect);
+        assert!(!board.chain().is_closed());
+        assert!(!board.is_connected(Cell::new(2, 2)));
+    }
+
+    #[test]
+    fn chains_retract_and_cancel() {
+        let mut board = board_from_rows(&["RRG", "RRG", "GGR"]);
+
+        board.start_chain(Cell::new(0, 0));
+        board.extend_chain(Cell::new(1, 0));
+        board.extend_chain(Cell::new(1, 1));
+        board.extend_chain(Cell::new(0, 1));
+        board.extend_chain(Cell::new(0, 0));
+
+        assert!(board.retract_chain());
+        assert!(!board.chain().is_closed());
+        assert_eq!(board.chain().last(), Some(Cell::new(0, 1)));
+
+        let before = board.field.clone();
+        assert!(board.cancel_chain());
+        assert!(board.chain().is_empty());
+        assert_eq!(board.field, before);
+        assert!(!board.cancel_chain());
+
+        board.start_chain(Cell::new(2, 0));
+        assert!(!board.retract_chain());
+        assert_eq!(board.chain().len(), 1);
+    }
+
+    #[test]
+    fn commit_clears_and_drops_points() {
+        let mut board = board_from_rows(&["RGB", "RGB", "GBR"]);
+
+        board.start_chain(Cell::new(0, 0));
+        board.extend_chain(Cell::new(0, 1));
+
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.color, Color::Red);
+        assert_eq!(clear.cleared, vec![Cell::new(0, 0), Cell::new(0, 1)]);
+        assert_eq!(clear.counts, vec![(Color::Red, 2)]);
+        assert_eq!(clear.count(Color::Red), 2);
+        assert_eq!(clear.count(Color::Blue), 0);
+        assert_eq!(clear.spawned, vec![Cell::new(0, 0), Cell::new(0, 1)]);
+        assert!(clear.falls.is_empty());
+        assert_eq!(board.color(Cell::new(0, 2)), Some(Color::Green));
+        assert!(board.chain().is_empty());
+
+        board.start_chain(Cell::new(1, 1));
+        board.extend_chain(Cell::new(1, 0));
+
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.falls, vec![]);
+        assert_eq!(board.color(Cell::new(1, 2)), Some(Color::Blue));
+
+        let mut board = board_from_rows(&["RGB", "GGB", "RBR"]);
+
+        board.start_chain(Cell::new(0, 1));
+        board.extend_chain(Cell::new(1, 1));
+
+        let clear = board.commit_chain().unwrap();
+
+        assert_eq!(clear.falls, vec![
+            Fall { from: Cell::new(0, 0), to: Cell::new(0, 1) },
+            Fall { from: Cell::new(1, 0), to: Cell::new(1, 1) },
+        ]);
+        assert_eq!(board.color(Cell::new(0, 1)), Some(Color::Red));
+        assert_eq!(board.color(Cell::new(1, 1)), Some(Color::Green));
+    }
+
+    #[test]
+    fn single_point_commit_is_dropped() {
+        let mut board = board_from_rows(&["RG", "GR"]);
+        let before = colors(&board);
+
+        board.start_chain(Cell::new(0, 0));
+
+        assert_eq!(board.commit_chain(), None);
+        assert_eq!(colors(&board), before);
+        assert!(board.chain().is_empty());
+    }
+
+    #[test]
+    fn new_boards_always_have_a_move() {
+        let config = Config { width: 3, height: 3, colors: Color::ALL.to_vec(), ..Config::default() };
+
+        assert!((0..200).all(|seed| Board::new(&config, seed).has_move()));
+        assert!(!board_from_rows(&["RGB", "GBR", "BRG"]).has_move());
+        assert!(board_from_rows(&["RGB", "GBB"]).has_move());
+        assert!(!board_from_rows(&["R#", "#R"]).has_move());
+    }
+
+    #[test]
+    fn stuck_boards_are_shuffled() {
+        let mut board = board_from_rows(&["RGB", "GBR", "BRG"]);
+        let before = board.clone();
+        let count = |board: &Board, color| board.cells().filter(|&cell| board.color(cell) == Some(color)).count();
+
+        let moves = board.shuffle();
+
+        assert!(board.has_move());
+        assert!(!moves.is_empty());
+        assert!([Color::Red, Color::Green, Color::Blue].iter().all(|&color| count(&board, color) == 3));
+        assert!(moves.iter().all(|fall| board.color(fall.to) == before.color(fall.from)));
+        assert!(board.shuffle().is_empty());
+    }
+
+    #[test]
+    fn boards_without_a_possible_move_stay_as_they_are() {
+        let mut board = board_from_rows(&["RG#", "#BP"]);
+
+        assert!(board.shuffle().is_empty());
+        assert_eq!(colors(&board), colors(&board_from_rows(&["RG#", "#BP"])));
+    }
+
+    #[test]
+    fn chains_follow_the_drawing_rules() {
+        let board = board_from_rows(&["RRG", "RRB", "BGB"]);
+        let chains = board.chains(1000);
+
+        assert!(chains.iter().all(|chain| {
+            let mut drawn = board.clone();
+            drawn.start_chain(chain.cells()[0]);
+            chain.cells()[1..].iter().all(|&cell| drawn.extend_chain(cell) == Step::Connect) && drawn.chain() == chain
+        }));
+        assert!(chains.iter().all(|chain| chain.len() >= 2));
+        assert!(chains.iter().any(Chain::is_closed));
+        assert_eq!(chains.iter().filter(|chain| chain.color() == Some(Color::Blue)).count(), 1);
+        assert_eq!(chains.iter().filter(|chain| chain.color() == Some(Color::Green)).count(), 0);
+    }
+
+    #[test]
+    fn chain_search_is_limited() {
+        let board = board_from_rows(&["RRRRRR"; 6]);
+        let chains = board.chains(720);
+
+        assert!(!chains.is_empty());
+        assert!(chains.len() <= 720);
+        assert!(chains.iter().any(|chain| chain.len() >= 10));
+    }
+}
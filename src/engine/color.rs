@@ -0,0 +1,107 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Dot colors. The discriminant is the column of the dot in `assets/cats.png`.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Color {
+    Purple,
+    Blue,
+    Green,
+    Red,
+    Cyan,
+    Orange,
+    Brown,
+    Gray,
+}
+
+impl Color {
+    pub const ALL: [Color; 8] = [
+        Color::Purple,
+        Color::Blue,
+        Color::Green,
+        Color::Red,
+        Color::Cyan,
+        Color::Orange,
+        Color::Brown,
+        Color::Gray,
+    ];
+
+    /// Colors a board uses unless told otherwise.
+    pub const DEFAULT: [Color; 5] = [Color::Purple, Color::Blue, Color::Green, Color::Red, Color::Cyan];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Color::Purple => "purple",
+            Color::Blue => "blue",
+            Color::Green => "green",
+            Color::Red => "red",
+            Color::Cyan => "cyan",
+            Color::Orange => "orange",
+            Color::Brown => "brown",
+            Color::Gray => "gray",
+        }
+    }
+
+    /// Single letter used for the color in level layouts.
+    pub fn symbol(self) -> char {
+        match self {
+            Color::Purple => 'P',
+            Color::Blue => 'B',
+            Color::Green => 'G',
+            Color::Red => 'R',
+            Color::Cyan => 'C',
+            Color::Orange => 'O',
+            Color::Brown => 'N',
+            Color::Gray => 'Y',
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Color> {
+        Color::ALL.iter().find(|color| color.symbol() == symbol.to_ascii_uppercase()).copied()
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Color::ALL.iter()
+            .find(|color| color.name().eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| format!("unknown color `{}`", name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn index_matches_sprite_column() {
+        Color::ALL.iter().enumerate().for_each(|(column, color)| assert_eq!(color.index(), column));
+    }
+
+    #[test]
+    fn symbols_are_unique() {
+        Color::ALL.iter().for_each(|&color| assert_eq!(Color::from_symbol(color.symbol()), Some(color)));
+        assert_eq!(Color::from_symbol('r'), Some(Color::Red));
+        assert_eq!(Color::from_symbol('#'), None);
+    }
+
+    #[test]
+    fn parses_names() {
+        Color::ALL.iter().for_each(|&color| assert_eq!(color.name().parse(), Ok(color)));
+        assert_eq!("Red".parse(), Ok(Color::Red));
+        assert!("pink".parse::<Color>().is_err());
+    }
+}
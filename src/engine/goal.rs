@@ -0,0 +1,66 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::engine::color::Color;
+
+/// Level objective: clear `target` dots of `color`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Goal {
+    pub color: Color,
+    pub target: u32,
+}
+
+impl Goal {
+    pub fn new(color: Color, target: u32) -> Self {
+        Goal { color, target }
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.color, self.target)
+    }
+}
+
+/// Parses `<color>:<count>`, e.g. `red:20`.
+impl FromStr for Goal {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.splitn(2, ':');
+        let color = parts.next().unwrap_or_default().trim().parse()?;
+        let target = parts.next()
+            .and_then(|target| target.trim().parse().ok())
+            .filter(|target| *target > 0)
+            .ok_or_else(|| format!("invalid goal `{}`, expected e.g. red:20", value))?;
+
+        Ok(Goal { color, target })
+    }
+}
+
+/// Where a game stands.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Outcome {
+    Playing,
+    /// Every goal was reached.
+    Won,
+    /// The moves or time ran out before the goals were reached.
+    Lost,
+    /// The moves or time ran out in a game without goals.
+    Finished,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_goals() {
+        assert_eq!("red:20".parse(), Ok(Goal::new(Color::Red, 20)));
+        assert_eq!("Blue : 15".parse(), Ok(Goal::new(Color::Blue, 15)));
+        assert_eq!(Goal::new(Color::Gray, 3).to_string().parse(), Ok(Goal::new(Color::Gray, 3)));
+        assert!("red".parse::<Goal>().is_err());
+        assert!("red:0".parse::<Goal>().is_err());
+        assert!("pink:3".parse::<Goal>().is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+
+use crate::engine::board::Clear;
+use crate::engine::session::Mode;
+
+/// What a committed chain is scored on.
+#[derive(Debug, Clone, Copy)]
+pub struct Play<'a> {
+    pub clear: &'a Clear,
+    /// Turns in a row that kept the combo going before this one.
+    pub combo: u32,
+    pub mode: Mode,
+}
+
+/// Why points were awarded.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Bonus {
+    /// One point per cleared dot.
+    Dots,
+    /// Long chains multiply the points for their dots.
+    Length,
+    /// Closed chains multiply everything before them.
+    Loop,
+    /// Consecutive turns that each kept the combo going.
+    Combo,
+}
+
+/// Points for a single part of a turn.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Part {
+    pub bonus: Bonus,
+    pub points: u64,
+}
+
+/// Every part a turn scored, in the order they were applied.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct Breakdown {
+    pub parts: Vec<Part>,
+    /// The combo streak after this turn, 0 when it was broken.
+    pub combo: u32,
+}
+
+impl Breakdown {
+    pub fn total(&self) -> u64 {
+        self.parts.iter().fold(0, |total, part| total.saturating_add(part.points))
+    }
+
+    pub fn points(&self, bonus: Bonus) -> u64 {
+        self.parts.iter().filter(|part| part.bonus == bonus).map(|part| part.points).sum()
+    }
+
+    fn add(&mut self, bonus: Bonus, points: u64) {
+        if points > 0 {
+            self.parts.push(Part { bonus, points });
+        }
+    }
+}
+
+/// Turns a committed chain into points. `Session` scores through this, so balancing
+/// only has to swap the policy.
+pub trait Scoring: fmt::Debug + Send + Sync {
+    fn score(&self, play: &Play) -> Breakdown;
+}
+
+/// The standard scoring policy.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Rules {
+    /// `(length, multiplier)` pairs by ascending length: chains of at least `length` cells
+    /// multiply the points for their dots.
+    pub length_multipliers: Vec<(usize, u32)>,
+    pub loop_multiplier: u32,
+    /// Chains of at least this many cells keep the combo going, shorter ones break it.
+    pub combo_length: usize,
+    /// Points per turn the combo has been going, awarded from the second turn on.
+    pub combo_points: u64,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            length_multipliers: vec![(6, 2), (9, 3)],
+            loop_multiplier: 2,
+            combo_length: 4,
+            combo_points: 5,
+        }
+    }
+}
+
+impl Rules {
+    /// Rules tuned for a mode: moves are scarce so loops pay more, timed games reward keeping combos up.
+    pub fn for_mode(mode: Mode) -> Self {
+        match mode {
+            Mode::Endless => Rules::default(),
+            Mode::Moves(_) => Rules { loop_multiplier: 3, ..Rules::default() },
+            Mode::Timed(_) => Rules { combo_points: 10, ..Rules::default() },
+        }
+    }
+
+    fn length_multiplier(&self, length: usize) -> u32 {
+        self.length_multipliers.iter()
+            .rev()
+            .find(|(from, _)| length >= *from)
+            .map_or(1, |(_, multiplier)| *multiplier)
+    }
+}
+
+impl Scoring for Rules {
+    fn score(&self, play: &Play) -> Breakdown {
+        let mut breakdown = Breakdown::default();
+        let length = play.clear.chain.len();
+
+        let dots = play.clear.cleared.len() as u64;
+        breakdown.add(Bonus::Dots, dots);
+        breakdown.add(Bonus::Length, dots * u64::from(self.length_multiplier(length).max(1) - 1));
+
+        if play.clear.closed {
+            breakdown.add(Bonus::Loop, breakdown.total() * u64::from(self.loop_multiplier.max(1) - 1));
+        }
+
+        if length >= self.combo_length {
+            breakdown.combo = play.combo + 1;
+            breakdown.add(Bonus::Combo, self.combo_points.saturating_mul(u64::from(play.combo)));
+        }
+
+        breakdown
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::board::test::board_from_rows;
+    use crate::engine::board::Cell;
+
+    fn clear(rows: &[&str], chain: &[(usize, usize)]) -> Clear {
+        let mut board = board_from_rows(rows);
+
+        board.start_chain(Cell::new(chain[0].0, chain[0].1));
+        chain[1..].iter().for_each(|&(x, y)| { board.extend_chain(Cell::new(x, y)); });
+
+        board.commit_chain().unwrap()
+    }
+
+    fn score(rules: &Rules, clear: &Clear, combo: u32) -> Breakdown {
+        rules.score(&Play { clear, combo, mode: Mode::Endless })
+    }
+
+    #[test]
+    fn short_chains_score_their_dots() {
+        let clear = clear(&["RRR", "GBG", "BGB"], &[(0, 0), (1, 0), (2, 0)]);
+        let breakdown = score(&Rules::default(), &clear, 3);
+
+        assert_eq!(breakdown.parts, vec![Part { bonus: Bonus::Dots, points: 3 }]);
+        assert_eq!(breakdown.total(), 3);
+        assert_eq!(breakdown.combo, 0);
+    }
+
+    #[test]
+    fn long_chains_multiply() {
+        let clear = clear(&["RRRRRR", "GBGBGB"], &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)]);
+        let breakdown = score(&Rules::default(), &clear, 0);
+
+        assert_eq!(breakdown.points(Bonus::Dots), 6);
+        assert_eq!(breakdown.points(Bonus::Length), 6);
+        assert_eq!(breakdown.combo, 1);
+        assert_eq!(breakdown.total(), 12);
+
+        let rules = Rules { length_multipliers: vec![(6, 0)], loop_multiplier: 0, ..Rules::default() };
+        assert_eq!(score(&rules, &clear, 0).total(), 6);
+    }
+
+    #[test]
+    fn loops_multiply_everything_before_them() {
+        let clear = clear(&["RRG", "RRB", "BGR"], &[(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]);
+        let breakdown = score(&Rules::default(), &clear, 0);
+
+        assert_eq!(breakdown.points(Bonus::Dots), 5);
+        assert_eq!(breakdown.points(Bonus::Loop), 5);
+
+        let breakdown = score(&Rules::for_mode(Mode::Moves(10)), &clear, 0);
+        assert_eq!(breakdown.points(Bonus::Loop), 10);
+    }
+
+    #[test]
+    fn combos_grow_with_each_turn() {
+        let clear = clear(&["RRRR", "GBGB"], &[(0, 0), (1, 0), (2, 0), (3, 0)]);
+        let rules = Rules::default();
+
+        assert_eq!(score(&rules, &clear, 0).points(Bonus::Combo), 0);
+        assert_eq!(score(&rules, &clear, 1).points(Bonus::Combo), 5);
+        assert_eq!(score(&rules, &clear, 2).points(Bonus::Combo), 10);
+        assert_eq!(score(&rules, &clear, 2).combo, 3);
+    }
+}
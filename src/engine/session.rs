@@ -0,0 +1,756 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::engine::board::{Board, Cell, Chain, Clear, Config, Step};
+use crate::engine::color::Color;
+use crate::engine::goal::{Goal, Outcome};
+use crate::engine::score::{Breakdown, Play, Rules, Scoring};
+
+/// Most turns kept to take back, even when undos are unlimited.
+pub const HISTORY_SIZE: usize = 50;
+/// Paths looked at for hints, see `Board::chains`.
+pub const HINT_SEARCH: usize = 4000;
+
+/// How a game ends.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum Mode {
+    /// Play until the window is closed.
+    #[default]
+    Endless,
+    /// Every committed chain uses up one of the given moves.
+    Moves(u32),
+    /// Score as much as possible before the clock runs out.
+    Timed(Duration),
+}
+
+/// `endless`, `moves 20` or `time 60`; timed modes count whole seconds.
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Endless => write!(f, "endless"),
+            Mode::Moves(moves) => write!(f, "moves {}", moves),
+            Mode::Timed(time) => write!(f, "time {}", time.as_secs()),
+        }
+    }
+}
+
+impl Mode {
+    /// Turns a game of this mode can take back by default, `None` for as many as it likes.
+    /// Only endless games get any, so undos don't change scores that are compared.
+    pub fn undo_limit(self) -> Option<u32> {
+        match self {
+            Mode::Endless => None,
+            Mode::Moves(_) | Mode::Timed(_) => Some(0),
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut words = text.split_whitespace();
+        let invalid = || format!("invalid mode `{}`, expected e.g. `endless`, `moves 20` or `time 60`", text);
+        let mode = match (words.next(), words.next().map(str::parse::<u64>)) {
+            (Some("endless"), None) => Mode::Endless,
+            (Some("moves"), Some(Ok(moves))) if moves <= u64::from(u32::MAX) => Mode::Moves(moves as u32),
+            (Some("time"), Some(Ok(seconds))) => Mode::Timed(Duration::from_secs(seconds)),
+            _ => return Err(invalid()),
+        };
+
+        if words.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(mode)
+    }
+}
+
+/// Everything a session has scored so far, for saving a game and picking it up later.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct Progress {
+    pub score: u64,
+    pub combo: u32,
+    pub moves: u32,
+    pub elapsed: Duration,
+    /// Dots cleared per color.
+    pub cleared: Vec<(Color, u32)>,
+    /// Turns taken back.
+    pub undos: u32,
+}
+
+/// A committed chain and the points it scored.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Turn {
+    pub clear: Clear,
+    pub breakdown: Breakdown,
+}
+
+/// A chain worth drawing and the points it would score.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Hint {
+    pub chain: Chain,
+    pub points: u64,
+}
+
+/// The game as it was before a turn, to go back to.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    board: Board,
+    score: u64,
+    combo: u32,
+    moves: u32,
+    cleared: Vec<(Color, u32)>,
+}
+
+/// A single game: the board and everything scored on it so far.
+#[derive(Debug, Clone)]
+pub struct Session {
+    score: u64,
+    combo: u32,
+    scoring: Arc<dyn Scoring>,
+    mode: Mode,
+    moves: u32,
+    elapsed: Duration,
+    goals: Vec<Goal>,
+    cleared: Vec<(Color, u32)>,
+    undo_limit: Option<u32>,
+    undos: u32,
+    /// Oldest first.
+    history: Vec<Snapshot>,
+    board: Board,
+}
+
+impl Session {
+    pub fn new(config: &Config, mode: Mode, seed: u64) -> Self {
+        Session::with_board(Board::new(config, seed), mode)
+    }
+
+    pub fn with_board(board: Board, mode: Mode) -> Self {
+        Session {
+            score: 0,
+            combo: 0,
+            scoring: Arc::new(Rules::for_mode(mode)),
+            mode,
+            moves: 0,
+            elapsed: Duration::from_secs(0),
+            goals: Vec::new(),
+            cleared: Vec::new(),
+            undo_limit: mode.undo_limit(),
+            undos: 0,
+            history: Vec::new(),
+            board,
+        }
+    }
+
+    /// Turns the game into a level that is won once every goal is reached.
+    pub fn with_goals(mut self, goals: Vec<Goal>) -> Self {
+        self.goals = goals;
+        self
+    }
+
+    /// Scores the game with another policy instead of the mode's `Rules`.
+    pub fn with_scoring(mut self, scoring: Arc<dyn Scoring>) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Allows `limit` undos instead of the mode's, `None` for unlimited.
+    pub fn with_undo_limit(mut self, limit: Option<u32>) -> Self {
+        self.undo_limit = limit;
+        self
+    }
+
+    /// Continues a saved game from where it was.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.score = progress.score;
+        self.combo = progress.combo;
+        self.moves = progress.moves;
+        self.elapsed = progress.elapsed;
+        self.cleared = progress.cleared;
+        self.undos = progress.undos;
+        self
+    }
+
+    /// A copy of the game whose board draws the dots to come from `seed`, see `Board::reseeded`.
+    pub fn reseeded(&self, seed: u64) -> Self {
+        Session { board: self.board.reseeded(seed), ..self.clone() }
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            score: self.score,
+            combo: self.combo,
+            moves: self.moves,
+            elapsed: self.elapsed,
+            cleared: self.cleared.clone(),
+            undos: self.undos,
+        }
+    }
+
+    pub fn goals(&self) -> &[Goal] {
+        &self.goals
+    }
+
+    /// Dots of `color` cleared so far.
+    pub fn cleared(&self, color: Color) -> u32 {
+        self.cleared.iter().find(|(counted, _)| *counted == color).map_or(0, |(_, count)| *count)
+    }
+
+    /// Dots of the goal's color still to clear.
+    pub fn remaining(&self, goal: &Goal) -> u32 {
+        goal.target.saturating_sub(self.cleared(goal.color))
+    }
+
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    /// Turns in a row that kept the combo going.
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Number of committed chains so far.
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    pub fn moves_left(&self) -> Option<u32> {
+        match self.mode {
+            Mode::Moves(limit) => Some(limit.saturating_sub(self.moves)),
+            _ => None,
+        }
+    }
+
+    pub fn undo_limit(&self) -> Option<u32> {
+        self.undo_limit
+    }
+
+    /// Undos still allowed, `None` when they are unlimited.
+    pub fn undos_left(&self) -> Option<u32> {
+        self.undo_limit.map(|limit| limit.saturating_sub(self.undos))
+    }
+
+    /// Whether there is a turn to take back and an undo left to do it with.
+    pub fn can_undo(&self) -> bool {
+        !self.is_over() && self.undos_left() != Some(0) && !self.history.is_empty()
+    }
+
+    /// Game time played so far.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn time_left(&self) -> Option<Duration> {
+        match self.mode {
+            Mode::Timed(limit) => Some(limit.checked_sub(self.elapsed).unwrap_or_default()),
+            _ => None,
+        }
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        if !self.goals.is_empty() && self.goals.iter().all(|goal| self.remaining(goal) == 0) {
+            return Outcome::Won;
+        }
+
+        if self.moves_left() != Some(0) && self.time_left() != Some(Duration::from_secs(0)) {
+            return Outcome::Playing;
+        }
+
+        if self.goals.is_empty() {
+            Outcome::Finished
+        } else {
+            Outcome::Lost
+        }
+    }
+
+    /// Whether the game has ended, the board takes no more input once it has.
+    pub fn is_over(&self) -> bool {
+        self.outcome() != Outcome::Playing
+    }
+
+    /// Advances the game clock by the real time that passed since the last tick.
+    pub fn tick(&mut self, delta: Duration) {
+        if self.is_over() {
+            return;
+        }
+
+        self.elapsed += delta;
+
+        if self.is_over() {
+            self.board.reset_chain();
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn start_chain(&mut self, cell: Cell) -> bool {
+        if self.is_over() {
+            return false;
+        }
+
+        self.board.start_chain(cell)
+    }
+
+    pub fn extend_chain(&mut self, cell: Cell) -> Step {
+        if self.is_over() {
+            return Step::None;
+        }
+
+        self.board.extend_chain(cell)
+    }
+
+    pub fn retract_chain(&mut self) -> bool {
+        !self.is_over() && self.board.retract_chain()
+    }
+
+    pub fn cancel_chain(&mut self) -> bool {
+        !self.is_over() && self.board.cancel_chain()
+    }
+
+    pub fn commit_chain(&mut self) -> Option<Turn> {
+        if self.is_over() {
+            return None;
+        }
+
+        let before = self.snapshot();
+        let clear = self.board.commit_chain()?;
+        let breakdown = self.scoring.score(&Play { clear: &clear, combo: self.combo, mode: self.mode });
+
+        self.moves += 1;
+        self.combo = breakdown.combo;
+        self.score = self.score.saturating_add(breakdown.total());
+        self.count_cleared(&clear);
+
+        if self.undos_left() != Some(0) {
+            if self.history.len() == HISTORY_SIZE {
+                self.history.remove(0);
+            }
+            self.history.push(before);
+        }
+
+        Some(Turn { clear, breakdown })
+    }
+
+    /// Chains that can be drawn among the first `limit` found, best first: the most points,
+    /// then loops, then the quickest to draw.
+    pub fn hints(&self, limit: usize) -> Vec<Hint> {
+        if self.is_over() {
+            return Vec::new();
+        }
+
+        let mut hints: Vec<Hint> = self.board.chains(limit).into_iter()
+            .filter_map(|chain| {
+                let mut board = self.board.clone();
+                let cells = chain.cells();
+
+                board.start_chain(cells[0]);
+                cells[1..].iter().for_each(|&cell| { board.extend_chain(cell); });
+                let clear = board.commit_chain()?;
+                let points = self.scoring.score(&Play { clear: &clear, combo: self.combo, mode: self.mode }).total();
+
+                Some(Hint { chain, points })
+            })
+            .collect();
+
+        hints.sort_by(|a, b| {
+            b.points.cmp(&a.points)
+                .then(b.chain.is_closed().cmp(&a.chain.is_closed()))
+                .then(a.chain.len().cmp(&b.chain.len()))
+        });
+
+        hints
+    }
+
+    /// The best chain to draw now.
+    pub fn hint(&self) -> Option<Hint> {
+        self.hints(HINT_SEARCH).into_iter().next()
+    }
+
+    /// Takes back the last turn: the board, including the dots still to come, the score,
+    /// the combo and the moves go back to before it. The game clock keeps running.
+    pub fn undo(&mut self) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+
+        let snapshot = match self.history.pop() {
+            Some(snapshot) => snapshot,
+            None => return false,
+        };
+
+        self.board = snapshot.board;
+        self.board.reset_chain();
+        self.score = snapshot.score;
+        self.combo = snapshot.combo;
+        self.moves = snapshot.moves;
+        self.cleared = snapshot.cleared;
+        self.undos += 1;
+
+        true
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            board: self.board.clone(),
+            score: self.score,
+            combo: self.combo,
+            moves: self.moves,
+            cleared: self.cleared.clone(),
+        }
+    }
+
+    fn count_cleared(&mut self, clear: &Clear) {
+        clear.counts.iter().for_each(|&(color, count)| {
+            match self.cleared.iter_mut().find(|(counted, _)| *counted == color) {
+                Some((_, total)) => *total += count as u32,
+                None => self.cleared.push((color, count as u32)),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::board::test::board_from_rows;
+    use crate::engine::score::{Bonus, Part};
+
+    #[test]
+    fn modes_roundtrip_through_text() {
+        [Mode::Endless, Mode::Moves(20), Mode::Timed(Duration::from_secs(60))].iter().for_each(|mode| {
+            assert_eq!(mode.to_string().parse(), Ok(*mode));
+        });
+        assert!("moves".parse::<Mode>().is_err());
+        assert!("time 60 s".parse::<Mode>().is_err());
+    }
+
+    #[test]
+    fn commit_scores_cleared_points() {
+        let mut session = Session::with_board(board_from_rows(&["RRR", "GBG", "BGB"]), Mode::Endless);
+
+        session.start_chain(Cell::new(0, 0));
+        session.extend_chain(Cell::new(1, 0));
+        session.extend_chain(Cell::new(2, 0));
+
+        assert!(session.commit_chain().is_some());
+        assert_eq!(session.score(), 3);
+
+        session.start_chain(Cell::new(0, 1));
+
+        assert_eq!(session.commit_chain(), None);
+        assert_eq!(session.score(), 3);
+    }
+
+    #[test]
+    fn loops_score_a_bonus() {
+        let mut session = Session::with_board(board_from_rows(&["RRG", "RRB", "BGR"]), Mode::Endless);
+
+        session.start_chain(Cell::new(0, 0));
+        session.extend_chain(Cell::new(1, 0));
+        session.extend_chain(Cell::new(1, 1));
+        session.extend_chain(Cell::new(0, 1));
+        session.extend_chain(Cell::new(0, 0));
+        let turn = session.commit_chain().unwrap();
+
+        assert_eq!(turn.clear.cleared.len(), 5);
+        assert_eq!(turn.breakdown.points(Bonus::Loop), 5);
+        assert_eq!(session.score(), 10);
+    }
+
+    #[derive(Debug)]
+    struct Flat;
+
+    impl Scoring for Flat {
+        fn score(&self, _: &Play) -> Breakdown {
+            Breakdown { parts: vec![Part { bonus: Bonus::Dots, points: 100 }], combo: 0 }
+        }
+    }
+
+    #[test]
+    fn scores_with_any_policy() {
+        let mut session = Session::with_board(board_from_rows(&["RRG", "GBB"]), Mode::Endless)
+            .with_scoring(Arc::new(Flat));
+
+        session.start_chain(Cell::new(0, 0));
+        session.extend_chain(Cell::new(1, 0));
+        session.commit_chain();
+
+        assert_eq!(session.score(), 100);
+    }
+
+    #[test]
+    fn score_is_not_capped() {
+        let mut session = Session::with_board(board_from_rows(&["RRG", "GBB"]), Mode::Endless)
+            .with_scoring(Arc::new(Flat));
+
+        session.score = 9990;
+        session.start_chain(Cell::new(0, 0));
+        session.extend_chain(Cell::new(1, 0));
+        session.commit_chain();
+        assert_eq!(session.score(), 10_090);
+
+        session.score = u64::MAX - 10;
+        session.start_chain(Cell::new(1, 1));
+        session.extend_chain(Cell::new(2, 1));
+        session.commit_chain();
+        assert_eq!(session.score(), u64::MAX);
+    }
+
+    #[test]
+    fn combo_carries_over_between_turns() {
+        let mut session = Session::with_board(board_from_rows(&["RRRR", "GGGG", "BBBB", "RGBR"]), Mode::Endless);
+
+        session.start_chain(Cell::new(0, 2));
+        (1..4).for_each(|x| { session.extend_chain(Cell::new(x, 2)); });
+        session.commit_chain();
+        assert_eq!(session.combo(), 1);
+
+        session.start_chain(Cell::new(0, 2));
+        (1..4).for_each(|x| { session.extend_chain(Cell::new(x, 2)); });
+        let turn = session.commit_chain().unwrap();
+        assert_eq!(session.combo(), 2);
+        assert_eq!(turn.breakdown.points(Bonus::Combo), 5);
+
+        session.start_chain(Cell::new(0, 3));
+        session.extend_chain(Cell::new(0, 2));
+        session.commit_chain();
+        assert_eq!(session.combo(), 0);
+    }
+
+    #[test]
+    fn moves_mode_locks_input_when_out_of_moves() {
+        let mut session = Session::with_board(board_from_rows(&["RRR", "GGG", "BBB"]), Mode::Moves(2));
+
+        assert_eq!(session.moves_left(), Some(2));
+
+        session.start_chain(Cell::new(0, 0));
+        assert_eq!(session.commit_chain(), None);
+        assert_eq!(session.moves_left(), Some(2));
+
+        session.start_chain(Cell::new(0, 2));
+        session.extend_chain(Cell::new(1, 2));
+        session.commit_chain();
+        assert_eq!(session.moves_left(), Some(1));
+        assert!(!session.is_over());
+
+        session.start_chain(Cell::new(0, 1));
+        session.extend_chain(Cell::new(1, 1));
+        session.commit_chain();
+        assert_eq!(session.moves_left(), Some(0));
+        assert!(session.is_over());
+
+        let score = session.score();
+
+        assert!(!session.start_chain(Cell::new(0, 0)));
+        assert_eq!(session.extend_chain(Cell::new(1, 0)), Step::None);
+        assert_eq!(session.commit_chain(), None);
+        assert_eq!(session.score(), score);
+    }
+
+    #[test]
+    fn timed_mode_ends_when_clock_runs_out() {
+        let mut session = Session::with_board(board_from_rows(&["RRR", "GGG", "BBB"]), Mode::Timed(Duration::from_secs(30)));
+
+        session.tick(Duration::from_secs(10));
+        assert_eq!(session.time_left(), Some(Duration::from_secs(20)));
+        assert_eq!(session.moves_left(), None);
+
+        session.start_chain(Cell::new(0, 2));
+        session.extend_chain(Cell::new(1, 2));
+        session.commit_chain();
+        assert_eq!(session.score(), 2);
+
+        session.start_chain(Cell::new(0, 2));
+        session.extend_chain(Cell::new(1, 2));
+        session.tick(Duration::from_millis(20_500));
+
+        assert!(session.is_over());
+        assert_eq!(session.time_left(), Some(Duration::from_secs(0)));
+        assert_eq!(session.elapsed(), Duration::from_millis(30_500));
+        assert!(session.board().chain().is_empty());
+        assert!(!session.start_chain(Cell::new(0, 0)));
+        assert_eq!(session.commit_chain(), None);
+        assert_eq!(session.score(), 2);
+
+        session.tick(Duration::from_secs(1));
+        assert_eq!(session.elapsed(), Duration::from_millis(30_500));
+    }
+
+    #[test]
+    fn goals_win_the_game() {
+        let board = board_from_rows(&["RRB", "RRB", "GGB"]);
+        let goals = vec![Goal::new(Color::Red, 4), Goal::new(Color::Blue, 3)];
+        let mut session = Session::with_board(board, Mode::Moves(5)).with_goals(goals.clone());
+
+        session.start_chain(Cell::new(2, 0));
+        session.extend_chain(Cell::new(2, 1));
+        session.extend_chain(Cell::new(2, 2));
+        session.commit_chain();
+
+        assert_eq!(session.cleared(Color::Blue), 3);
+        assert_eq!(session.remaining(&goals[1]), 0);
+        assert_eq!(session.remaining(&goals[0]), 4);
+        assert_eq!(session.outcome(), Outcome::Playing);
+
+        session.start_chain(Cell::new(0, 0));
+        session.extend_chain(Cell::new(1, 0));
+        session.extend_chain(Cell::new(1, 1));
+        session.extend_chain(Cell::new(0, 1));
+        session.extend_chain(Cell::new(0, 0));
+        session.commit_chain();
+
+        assert!(session.cleared(Color::Red) >= 4);
+        assert_eq!(session.outcome(), Outcome::Won);
+        assert!(session.is_over());
+        assert_eq!(session.moves_left(), Some(3));
+    }
+
+    #[test]
+    fn goals_lose_when_out_of_moves() {
+        let board = board_from_rows(&["RRB", "GGB", "GGB"]);
+        let mut session = Session::with_board(board, Mode::Moves(1)).with_goals(vec![Goal::new(Color::Red, 20)]);
+
+        session.start_chain(Cell::new(0, 0));
+        session.extend_chain(Cell::new(1, 0));
+        session.commit_chain();
+
+        assert_eq!(session.outcome(), Outcome::Lost);
+
+        let mut session = Session::with_board(board_from_rows(&["RRB"]), Mode::Moves(1));
+
+        session.start_chain(Cell::new(0, 0));
+        session.extend_chain(Cell::new(1, 0));
+        session.commit_chain();
+
+        assert_eq!(session.outcome(), Outcome::Finished);
+    }
+
+    #[test]
+    fn endless_mode_never_ends() {
+        let mut session = Session::with_board(board_from_rows(&["RRR", "GGG", "BBB"]), Mode::Endless);
+
+        session.start_chain(Cell::new(0, 1));
+        session.extend_chain(Cell::new(1, 1));
+        session.commit_chain();
+
+        session.tick(Duration::from_secs(3600));
+
+        assert_eq!(session.moves(), 1);
+        assert_eq!(session.moves_left(), None);
+        assert_eq!(session.time_left(), None);
+        assert!(!session.is_over());
+    }
+
+    #[test]
+    fn same_seed_and_moves_replay_the_same_game() {
+        let play = |seed| {
+            let config = Config { width: 6, height: 4, ..Config::default() };
+            let mut session = Session::new(&config, Mode::Endless, seed);
+
+            for _ in 0..20 {
+                let board = session.board();
+                let pair = board.cells()
+                    .flat_map(|cell| board.cells().map(move |other| (cell, other)))
+                    .find(|&(cell, other)| cell.is_sibling(other) && board.color(cell) == board.color(other));
+
+                if let Some((cell, other)) = pair {
+                    session.start_chain(cell);
+                    session.extend_chain(other);
+                    session.commit_chain();
+                }
+            }
+
+            let board = session.board();
+            (session.score(), board.cells().map(|cell| board.color(cell)).collect::<Vec<_>>())
+        };
+
+        assert_eq!(play(7), play(7));
+    }
+
+    /// Clears the first two neighbouring dots of a color.
+    fn play(session: &mut Session) -> Turn {
+        let board = session.board();
+        let (cell, other) = board.cells()
+            .flat_map(|cell| board.cells().map(move |other| (cell, other)))
+            .find(|&(cell, other)| cell.is_sibling(other) && board.color(cell).is_some() && board.color(cell) == board.color(other))
+            .unwrap();
+
+        session.start_chain(cell);
+        session.extend_chain(other);
+        session.commit_chain().unwrap()
+    }
+
+    #[test]
+    fn undo_takes_back_the_last_turn() {
+        let mut session = Session::new(&Config { width: 4, height: 4, ..Config::default() }, Mode::Endless, 3);
+        let colors = |session: &Session| session.board().cells().map(|cell| session.board().color(cell)).collect::<Vec<_>>();
+
+        play(&mut session);
+        let before = (session.progress(), colors(&session));
+        let turn = play(&mut session);
+
+        assert!(session.undo());
+        assert_eq!((session.progress(), colors(&session)), (Progress { undos: 1, ..before.0.clone() }, before.1));
+
+        // The dots that fell in come again, so undoing does not reroll the board.
+        assert_eq!(play(&mut session), turn);
+
+        assert!(session.undo());
+        assert!(session.undo());
+        assert_eq!(session.score(), 0);
+        assert!(!session.undo());
+    }
+
+    #[test]
+    fn undos_are_limited_by_mode() {
+        let two_colors = Config { width: 6, height: 6, colors: vec![Color::Red, Color::Blue], ..Config::default() };
+
+        let mut session = Session::new(&two_colors, Mode::Moves(5), 1);
+        play(&mut session);
+        assert_eq!(session.undos_left(), Some(0));
+        assert!(!session.undo());
+
+        let mut session = Session::new(&two_colors, Mode::Moves(5), 1).with_undo_limit(Some(1));
+        play(&mut session);
+        play(&mut session);
+        assert!(session.undo());
+        assert_eq!(session.moves_left(), Some(4));
+        assert!(!session.undo());
+        assert_eq!(session.undos_left(), Some(0));
+
+        let mut session = Session::new(&two_colors, Mode::Moves(1), 1).with_undo_limit(None);
+        play(&mut session);
+        assert!(session.is_over());
+        assert!(!session.undo());
+
+        let mut session = Session::new(&two_colors, Mode::Endless, 1);
+        (0..HISTORY_SIZE + 5).for_each(|_| { play(&mut session); });
+        assert_eq!((0..).take_while(|_| session.undo()).count(), HISTORY_SIZE);
+    }
+
+    #[test]
+    fn hints_rank_chains_by_points() {
+        let session = Session::with_board(board_from_rows(&["RRG", "RRB", "BGB"]), Mode::Moves(1));
+        let hints = session.hints(1000);
+
+        assert!(hints.windows(2).all(|pair| pair[0].points >= pair[1].points));
+        assert_eq!(session.hint(), hints.first().cloned());
+        assert!(hints[0].chain.is_closed());
+        assert_eq!(hints[0].points, 12);
+
+        let mut session = session;
+        play(&mut session);
+        assert!(session.is_over());
+        assert_eq!(session.hint(), None);
+    }
+}
@@ -1,19 +1,43 @@
 pub(crate) mod board;
 pub(crate) mod color;
+pub(crate) mod cursor;
+pub(crate) mod effect;
+pub(crate) mod menu;
+pub(crate) mod number;
 pub(crate) mod point;
+pub(crate) mod scores;
+pub(crate) mod text;
 
-use sdl2::render::{Canvas, Texture};
-use sdl2::video::{Window, WindowContext};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::image::LoadTexture;
 
+use std::mem;
 use std::path::Path;
-use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cats::engine::{Cell, Mode, Outcome, Session, Turn};
+use cats::replay::{Action, Player, Replay};
+use cats::save::{SaveError, SavedGame};
+use cats::scores::{Entry, HighScores, Key};
 
 use board::Board;
-use crate::{TILE_SIZE, FIELD_OFFSET_LEFT, FIELD_OFFSET_TOP};
+use cursor::Direction;
+use menu::{Menu, Navigation};
+use text::{render_text, render_text_centered, text_width};
+use crate::FIELD_OFFSET_LEFT;
+use crate::input::Command;
 
-const SCORE_LIMIT: i32 = 9999;
+const TEXT_COLOR: Color = Color { r: 60, g: 60, b: 60, a: 0xff };
+const OVERLAY: Color = Color { r: 255, g: 250, b: 240, a: 0xe0 };
+/// Side of a digit in `numbers.png`.
+const DIGIT_SIZE: i32 = 16;
+/// Space kept between the score and the moves or time left.
+const SCORE_GAP: i32 = 16;
+/// Idle time before a chain is suggested, unless set otherwise.
+pub const HINT_DELAY: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Eq, PartialEq, PartialOrd, Hash, Clone, Copy)]
 pub struct Coordinates {
@@ -22,61 +46,533 @@ pub struct Coordinates {
 }
 
 pub struct Game {
-    score: i32,
+    name: String,
+    session: Session,
     board: Board,
+    scores: HighScores,
+    key: Key,
+    /// Whether the game went into the high scores already, and where it placed.
+    recorded: Option<Option<usize>>,
+    /// The high score table being looked at, if any.
+    browsing: Option<usize>,
+    /// A game from last time the player can go back to instead, with the buttons answering.
+    offer: Option<(SavedGame, Menu<bool>)>,
+    /// Every action on the board so far, when the game is recorded.
+    recording: Option<Replay>,
+    /// The replay being played back, the mouse does nothing until it ends.
+    playback: Option<(Player, Replay)>,
+    /// How long the player can sit idle before a chain is suggested, `None` for never.
+    hint_delay: Option<Duration>,
+    /// Time since the player last changed the board.
+    idle: Duration,
+    hinted: bool,
+    /// Shown once the keyboard is used, hidden again by the mouse.
+    cursor: Option<Cell>,
 }
 
 impl Game {
-    pub fn new() -> Self {
+    /// A game of the level called `name`, its result goes into the `key` table of `scores`.
+    pub fn new(name: String, session: Session, key: Key, scores: HighScores) -> Self {
+        let board = Board::new(session.board());
+
         Game {
-            score: 0,
-            board: Board::new(),
+            name,
+            session,
+            board,
+            scores,
+            key,
+            recorded: None,
+            browsing: None,
+            offer: None,
+            recording: None,
+            playback: None,
+            hint_delay: Some(HINT_DELAY),
+            idle: Duration::from_secs(0),
+            hinted: false,
+            cursor: None,
+        }
+    }
+
+    /// Suggests a chain once the player has been idle for `delay`, never with `None`.
+    pub fn show_hints_after(&mut self, delay: Option<Duration>) {
+        self.hint_delay = delay;
+
+        if delay.is_none() {
+            self.board.show_hint(None);
+        }
+    }
+
+    pub fn shows_hints(&self) -> bool {
+        self.hint_delay.is_some()
+    }
+
+    /// Leaves this game for another one, keeping the high scores and hint setting. Like on
+    /// quitting, an endless game goes into the high scores; recordings and replays stop.
+    pub fn start(&mut self, name: String, session: Session, key: Key) {
+        self.finish();
+
+        let scores = mem::replace(&mut self.scores, HighScores::in_memory());
+        let hint_delay = self.hint_delay;
+
+        *self = Game::new(name, session, key, scores);
+        self.hint_delay = hint_delay;
+    }
+
+    /// Records the game into `replay`, which must hold the level and seed of the session.
+    pub fn record(&mut self, replay: Replay) {
+        self.recording = Some(replay);
+    }
+
+    /// The recording so far, ended at the current game time.
+    pub fn take_recording(&mut self) -> Option<Replay> {
+        let mut replay = self.recording.take()?;
+        replay.finish(&self.session);
+
+        Some(replay)
+    }
+
+    /// Plays `replay` back on this game, which must have started from `replay.session()`.
+    pub fn play_back(&mut self, replay: Replay) {
+        self.playback = Some((Player::new(replay.events.clone()), replay));
+    }
+
+    /// Window title: the level and the seed to replay it with.
+    pub fn title(&self) -> String {
+        match self.name.as_str() {
+            "" => format!("cats #{}", self.session.board().seed()),
+            name => format!("cats - {} #{}", name, self.session.board().seed()),
+        }
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        if !self.is_paused() {
+            match &mut self.playback {
+                Some((player, _)) => {
+                    let until = self.session.elapsed() + delta;
+                    let board = &mut self.board;
+                    player.advance(&mut self.session, until, |session, action| {
+                        let (changed, turn) = action.apply(session);
+                        show(board, session, action, changed, turn);
+                    });
+                    self.end_playback();
+                },
+                None => self.session.tick(delta),
+            }
+        }
+
+        self.board.update(delta);
+        self.update_hint(delta);
+
+        if self.session.is_over() {
+            self.finish();
+        }
+    }
+
+    /// Counts the idle time while the player is not drawing, and suggests the best chain once it
+    /// reaches the hint delay.
+    fn update_hint(&mut self, delta: Duration) {
+        let waiting = self.playback.is_none() && !self.is_paused() && self.session.board().chain().is_empty();
+        if !waiting {
+            return;
+        }
+
+        self.idle += delta;
+
+        if !self.hinted && self.hint_delay.is_some_and(|delay| self.idle >= delay) {
+            self.hinted = true;
+            self.board.show_hint(self.session.hint().as_ref().map(|hint| &hint.chain));
+        }
+    }
+
+    /// Puts the game into the high scores, once. Ended games do this by themselves,
+    /// endless ones when the window is closed.
+    pub fn finish(&mut self) {
+        if self.recorded.is_some() || self.session.score() == 0 {
+            return;
+        }
+
+        if self.session.mode() != Mode::Endless && !self.session.is_over() {
+            return;
+        }
+
+        self.recorded = Some(self.scores.record(&self.key, entry(&self.session)));
+        self.save_scores();
+    }
+
+    fn save_scores(&self) {
+        if let Err(error) = self.scores.save() {
+            eprintln!("could not save high scores: {}", error);
+        }
+    }
+
+    /// Closing the window: a game still going is saved to `path` to be resumed next time,
+    /// without one it goes into the high scores as it is.
+    pub fn quit(&mut self, path: Option<&Path>) -> Result<(), SaveError> {
+        if self.offer.is_some() {
+            // Nothing was played yet, the offered game stays saved for next time.
+            return Ok(());
+        }
+
+        match path {
+            Some(path) if !self.session.is_over() => {
+                let saved = SavedGame { name: self.name.clone(), key: self.key.clone(), session: self.session.clone() };
+                saved.save(path)
+            },
+            _ => {
+                self.finish();
+                Ok(())
+            },
+        }
+    }
+
+    /// Asks whether to go back to `saved` before this game starts.
+    pub fn offer_resume(&mut self, saved: SavedGame) {
+        let buttons = vec![("RESUME".to_string(), true), ("NEW GAME".to_string(), false)];
+        self.offer = Some((saved, Menu::new("RESUME LAST GAME?", buttons)));
+    }
+
+    pub fn is_offering(&self) -> bool {
+        self.offer.is_some()
+    }
+
+    /// Moves around the buttons of the offer in a window of `size`, returns the answer once
+    /// one is picked. Backing out is a no.
+    pub fn navigate_offer(&mut self, navigation: Navigation, size: (u32, u32)) -> Option<bool> {
+        let (_, menu) = self.offer.as_mut()?;
+
+        match navigation {
+            Navigation::Back => Some(false),
+            navigation => menu.navigate(navigation, size),
+        }
+    }
+
+    /// Switches to the offered game, or drops it. An endless game that is dropped goes into
+    /// the high scores, the others did not end and don't count.
+    pub fn answer_resume(&mut self, resume: bool) {
+        let saved = match self.offer.take() {
+            Some((saved, _)) => saved,
+            None => return,
+        };
+
+        if resume {
+            self.name = saved.name;
+            self.key = saved.key;
+            self.board = Board::new(saved.session.board());
+            self.session = saved.session;
+            self.recorded = None;
+        } else if saved.session.mode() == Mode::Endless && saved.session.score() > 0 {
+            self.scores.record(&saved.key, entry(&saved.session));
+            self.save_scores();
+        }
+    }
+
+    /// Reports how the replay went once it is played through and hands the game to the player.
+    fn end_playback(&mut self) {
+        let done = match &self.playback {
+            Some((player, replay)) => player.is_done() && (self.session.elapsed() >= replay.length || self.session.is_over()),
+            None => false,
+        };
+
+        if let Some((_, replay)) = self.playback.take().filter(|_| done) {
+            eprintln!("replay scored {}, recorded {}", self.session.score(), replay.score);
+
+            if self.session.score() != replay.score {
+                eprintln!("the replay does not match its recording");
+            }
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.browsing.is_some() || self.offer.is_some()
+    }
+
+    /// Opens the high scores at the table of this game, or closes them.
+    fn toggle_scores(&mut self) {
+        self.browsing = match self.browsing {
+            Some(_) => None,
+            None => Some(self.scores.tables().iter().position(|table| table.key == self.key).unwrap_or(0)),
+        };
+    }
+
+    /// Flips through the high score tables while they are open.
+    fn browse(&mut self, step: i32) {
+        let count = self.scores.tables().len() as i32;
+
+        if let Some(index) = self.browsing.as_mut().filter(|_| count > 0) {
+            *index = (*index as i32 + step).rem_euclid(count) as usize;
         }
     }
 
-    pub fn update(&mut self) {
-        self.board.update();
+    pub fn is_browsing(&self) -> bool {
+        self.browsing.is_some()
     }
 
     pub fn render(&mut self, canvas: &mut Canvas<Window>) {
-        self.board.render(canvas);
-        self.render_score(canvas)
+        self.board.render(canvas, self.session.board());
+        if let Some(cell) = self.cursor.filter(|_| !self.session.is_over()) {
+            self.board.render_cursor(canvas, cell, TEXT_COLOR);
+        }
+        self.render_score(canvas);
+        self.render_limit(canvas);
+        self.render_goals(canvas);
+
+        if self.session.is_over() {
+            self.render_results(canvas);
+        }
+
+        if let Some(index) = self.browsing {
+            let tables = self.scores.tables();
+            scores::render_table(canvas, tables.get(index), index, tables.len());
+        }
+
+        if let Some((saved, menu)) = &self.offer {
+            Game::render_offer(canvas, saved, menu);
+        }
     }
 
-    pub fn handle_click(&mut self, x: i32, y: i32) {
-        self.board.handle_click(x, y);
+    /// Carries out a command of the player; pausing, quitting and answering the resume offer
+    /// are left to the caller.
+    pub fn handle(&mut self, command: Command) {
+        match command {
+            Command::Play(action) => self.play(action),
+            Command::Point(Some(Coordinates { x, y })) => {
+                self.cursor = None;
+                self.board.point_to(x, y);
+            },
+            Command::Point(None) => self.board.release(),
+            Command::Cursor(direction) => self.move_cursor(direction),
+            Command::Scores => self.toggle_scores(),
+            Command::Browse(step) => self.browse(step),
+            Command::Pause | Command::Quit | Command::Resume(_) | Command::Offer(_) => (),
+        }
     }
 
-    pub fn handle_move(&mut self, x: i32, y: i32) {
-        self.board.handle_move(x, y);
+    /// The cell the keyboard cursor is on, when it is showing.
+    pub fn cursor(&self) -> Option<Cell> {
+        self.cursor
     }
 
-    pub fn handle_raise(&mut self) {
-        let amount = self.board.handle_raise();
-        self.update_score(amount);
+    pub fn cell_at(&self, x: i32, y: i32) -> Option<Cell> {
+        self.board.cell_at(x, y)
+    }
+
+    /// Acts on the board, nothing during a replay. Paused games only finish off the chain
+    /// being drawn.
+    fn play(&mut self, action: Action) {
+        let drawing = matches!(action, Action::Extend(_) | Action::Retract | Action::Cancel | Action::Commit);
+        if self.playback.is_some() || (self.is_paused() && !drawing) {
+            return;
+        }
+
+        self.act(action);
+
+        // The cursor stays on the end of the chain it draws.
+        if let (Some(cursor), Some(last)) = (self.cursor.as_mut(), self.session.board().chain().last()) {
+            *cursor = last;
+        }
     }
 
-    fn update_score(&mut self, amount: i32) {
-        if self.score == SCORE_LIMIT {
+    /// Shows the keyboard cursor, or moves it a cell towards `direction`.
+    fn move_cursor(&mut self, direction: Option<Direction>) {
+        if self.is_paused() || self.playback.is_some() {
             return;
         }
 
-        if self.score + amount >= SCORE_LIMIT {
-            self.score = SCORE_LIMIT;
+        let board = self.session.board();
+        let (width, height) = (board.width(), board.height());
+
+        self.cursor = Some(match (self.cursor, direction) {
+            (Some(cell), Some(direction)) => cursor::step(cell, direction, width, height),
+            (Some(cell), None) => cell,
+            (None, _) => cursor::centered(width, height),
+        });
+    }
+
+    /// Applies an action of the player, recording it when it changed the board.
+    fn act(&mut self, action: Action) {
+        let (changed, turn) = action.apply(&mut self.session);
+
+        if let Some(replay) = self.recording.as_mut().filter(|_| changed) {
+            replay.record(self.session.elapsed(), action);
+        }
+
+        if changed {
+            self.idle = Duration::from_secs(0);
+            self.hinted = false;
+            self.board.show_hint(None);
         }
 
-        self.score += amount;
+        show(&mut self.board, &self.session, action, changed, turn);
     }
 
+    /// Score in the top left, shortened when it would run into the moves or time left.
     fn render_score(&mut self, canvas: &mut Canvas<Window>) {
         let texture_creator = canvas.texture_creator();
         let texture = texture_creator.load_texture(Path::new("assets/numbers.png")).unwrap();
 
-        self.score.to_string().as_str().chars().enumerate().for_each(|(index, symbol)| {
-            let offset = symbol.to_digit(10).unwrap() as i32;
-            let src = Rect::new(16 * offset, 0, 16, 16);
-            let target = Rect::new(16 * index as i32 + FIELD_OFFSET_LEFT, 20, 16, 16);
-            canvas.copy(&texture, src, target).unwrap();
+        let (width, _) = canvas.output_size().unwrap();
+        let limit = self.limit_label().map_or(0, |label| text_width(&label, 2) + SCORE_GAP);
+        let room = width as i32 - FIELD_OFFSET_LEFT * 2 - limit;
+        let text = number::fitting(self.session.score(), room, score_width);
+        let mut x = FIELD_OFFSET_LEFT;
+
+        text.chars().for_each(|symbol| match symbol.to_digit(10) {
+            Some(digit) => {
+                let src = Rect::new(DIGIT_SIZE * digit as i32, 0, DIGIT_SIZE as u32, DIGIT_SIZE as u32);
+                canvas.copy(&texture, src, Rect::new(x, 20, DIGIT_SIZE as u32, DIGIT_SIZE as u32)).unwrap();
+                x += DIGIT_SIZE;
+            },
+            None => {
+                let symbol = symbol.to_string();
+                render_text(canvas, &symbol, x + 1, 22, 2, TEXT_COLOR);
+                x += text_width(&symbol, 2) + 2;
+            },
+        });
+    }
+
+    fn limit_label(&self) -> Option<String> {
+        match (self.session.moves_left(), self.session.time_left()) {
+            (Some(moves), _) => Some(format!("MOVES {}", moves)),
+            (_, Some(time)) => {
+                let seconds = (time.as_millis() as u64).div_ceil(1000);
+                Some(format!("TIME {}:{:02}", seconds / 60, seconds % 60))
+            },
+            _ => None,
+        }
+    }
+
+    /// Moves or time left, drawn opposite the score.
+    fn render_limit(&self, canvas: &mut Canvas<Window>) {
+        let label = match self.limit_label() {
+            Some(label) => label,
+            None => return,
+        };
+
+        let (width, _) = canvas.output_size().unwrap();
+        let x = width as i32 - FIELD_OFFSET_LEFT - text_width(&label, 2);
+        render_text(canvas, &label, x, 22, 2, TEXT_COLOR);
+    }
+
+    /// A dot of each goal color with how many are still to clear.
+    fn render_goals(&self, canvas: &mut Canvas<Window>) {
+        let texture_creator = canvas.texture_creator();
+        let texture = texture_creator.load_texture(Path::new("assets/cats.png")).unwrap();
+        let mut x = FIELD_OFFSET_LEFT;
+
+        self.session.goals().iter().for_each(|goal| {
+            let src = Rect::new(goal.color.index() as i32 * 32, 0, 32, 32);
+            canvas.copy(&texture, src, Rect::new(x, 48, 24, 24)).unwrap();
+
+            let label = self.session.remaining(goal).to_string();
+            render_text(canvas, &label, x + 28, 53, 2, TEXT_COLOR);
+
+            x += 28 + text_width(&label, 2) + 16;
         });
     }
-}
\ No newline at end of file
+
+    /// The offer's buttons, with the game they would go back to above them.
+    fn render_offer(canvas: &mut Canvas<Window>, saved: &SavedGame, menu: &Menu<bool>) {
+        let size = canvas.output_size().unwrap();
+        let center = size.0 as i32 / 2;
+        let top = menu.title_y(size) - 64;
+
+        menu.render(canvas);
+
+        let room = size.0 as i32 - 40 - text_width("SCORE ", 2);
+        let score = format!("SCORE {}", number::fitting(saved.session.score(), room, |text| text_width(text, 2)));
+        render_text_centered(canvas, &saved.key.to_string(), center, top, 1, TEXT_COLOR);
+        render_text_centered(canvas, &score, center, top + 20, 2, TEXT_COLOR);
+    }
+
+    fn render_results(&self, canvas: &mut Canvas<Window>) {
+        let (width, height) = canvas.output_size().unwrap();
+        let center = width as i32 / 2;
+        let top = height as i32 / 2 - 60;
+
+        canvas.set_draw_color(OVERLAY);
+        canvas.fill_rect(Rect::new(0, 0, width, height)).unwrap();
+
+        let title = match self.session.outcome() {
+            Outcome::Won => "YOU WIN",
+            _ => "GAME OVER",
+        };
+
+        render_text_centered(canvas, title, center, top, 4, TEXT_COLOR);
+        render_text_centered(canvas, "FINAL SCORE", center, top + 56, 2, TEXT_COLOR);
+        let score = number::fitting(self.session.score(), width as i32 - 40, |text| text_width(text, 4));
+        render_text_centered(canvas, &score, center, top + 80, 4, TEXT_COLOR);
+
+        if let Some(Some(place)) = self.recorded {
+            render_text_centered(canvas, &format!("HIGH SCORE #{}", place + 1), center, top + 124, 2, TEXT_COLOR);
+            render_text_centered(canvas, "H HIGH SCORES", center, top + 148, 1, TEXT_COLOR);
+        }
+    }
+}
+
+/// Shows what `action` did to `session`: dots flying off and the points they made, or the
+/// board put back after an undo.
+fn show(board: &mut Board, session: &Session, action: Action, changed: bool, turn: Option<Turn>) {
+    if let Some(turn) = turn {
+        board.apply(&turn.clear);
+
+        if let Some(&last) = turn.clear.chain.last() {
+            board.show_score(&turn.breakdown, last, TEXT_COLOR);
+        }
+    } else if action == Action::Undo && changed {
+        *board = Board::new(session.board());
+    }
+}
+
+/// The high score entry of a game ending now.
+fn entry(session: &Session) -> Entry {
+    Entry {
+        score: session.score(),
+        played_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()),
+        duration: session.elapsed().as_secs(),
+        moves: session.moves(),
+        seed: session.board().seed(),
+    }
+}
+
+/// Width of the score drawn by `render_score`: digits from the sprite strip, anything else in the bitmap font.
+fn score_width(text: &str) -> i32 {
+    text.chars()
+        .map(|symbol| if symbol.is_ascii_digit() { DIGIT_SIZE } else { text_width(&symbol.to_string(), 2) + 2 })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cats::engine::Config;
+
+    #[test]
+    fn score_width_mixes_digits_and_font() {
+        assert_eq!(score_width("12"), 32);
+        assert_eq!(score_width("1,000"), 64 + 12);
+    }
+
+    #[test]
+    fn offers_are_answered_with_the_menu_buttons() {
+        let key = Key { level: "6x6".to_string(), mode: "moves 10".to_string(), seed: None };
+        let session = |seed| Session::new(&Config::default(), Mode::Moves(10), seed);
+        let mut game = Game::new(String::new(), session(1), key.clone(), HighScores::in_memory());
+        let size = (400, 500);
+
+        game.offer_resume(SavedGame { name: "Saved".to_string(), key, session: session(2) });
+        assert_eq!(game.navigate_offer(Navigation::Down, size), None);
+        assert_eq!(game.navigate_offer(Navigation::Choose, size), Some(false));
+        assert_eq!(game.navigate_offer(Navigation::Back, size), Some(false));
+        assert_eq!(game.navigate_offer(Navigation::Up, size), None);
+        assert_eq!(game.navigate_offer(Navigation::Choose, size), Some(true));
+
+        game.answer_resume(true);
+        assert!(!game.is_offering());
+        assert_eq!(game.name, "Saved");
+        assert_eq!(game.navigate_offer(Navigation::Choose, size), None);
+    }
+}
@@ -1,5 +1,5 @@
-use std::collections::LinkedList;
 use std::path::Path;
+use std::time::Duration;
 
 use sdl2::render::Canvas;
 use sdl2::video::Window;
@@ -7,367 +7,229 @@ use sdl2::pixels::Color;
 use sdl2::rect::{ Rect, Point as sdlPoint };
 use sdl2::image::LoadTexture;
 
+use cats::engine::{self, Bonus, Breakdown, Cell, Chain, Clear, Config};
+use cats::engine::board::BLAST_RADIUS;
+
 use crate::{TILE_SIZE, FIELD_OFFSET_LEFT, FIELD_OFFSET_TOP};
+use crate::game::color::{dot_color, WALL};
+use crate::game::effect::{Flash, Popup};
+use crate::game::number;
 use crate::game::point::Point;
 use crate::game::Coordinates;
 
-enum Action {
-    Connect,
-    Disconnect,
-    None,
-}
-
-#[derive(Debug, PartialEq)]
-pub struct Segment {
-    pub from: Coordinates,
-    pub to: Coordinates,
-}
-
+const LOOP_FLASH: Duration = Duration::from_millis(400);
+const BOMB_FLASH: Duration = Duration::from_millis(600);
+const POPUP_TIME: Duration = Duration::from_millis(900);
+const POPUP_SPACING: i32 = 20;
+/// Thickness of the keyboard cursor outline.
+const CURSOR_WIDTH: u32 = 3;
+/// One beat of a hinted dot growing and shrinking back.
+const HINT_PULSE: Duration = Duration::from_millis(800);
+/// Pixels a hinted dot grows by at the top of its pulse.
+const HINT_GROW: f32 = 5.0;
+
+/// Draws an `engine::Board` and keeps the sprites animating its dots.
 #[derive(Debug, PartialEq)]
 pub struct Board {
-    field: Vec<Vec<Option<Point>>>,
-    segments: LinkedList<Segment>,
-    closed_path: bool,
-    current_color: Option<Color>,
+    field: Vec<Vec<Point>>,
+    pointer: Option<Coordinates>,
+    flashes: Vec<Flash>,
+    popups: Vec<Popup>,
+    /// Dots of the suggested chain, pulsing.
+    hint: Vec<Cell>,
+    /// Time into the current pulse.
+    pulse: Duration,
 }
 
 impl Board {
-    pub fn new() -> Self {
-        let mut field = Vec::with_capacity(5);
-
-        for x in 0..5 {
-            let mut column: Vec<Option<Point>> = Vec::with_capacity(5);
-            for y in 0..5 {
-                let coords = Board::index_to_coordinates(x, y);
-                column.push(Some(Point::new(coords)));
-            }
+    /// Window size that fits a board of the given shape.
+    pub fn window_size(config: &Config) -> (u32, u32) {
+        let width = FIELD_OFFSET_LEFT * 2 + TILE_SIZE * config.width as i32;
+        let height = FIELD_OFFSET_TOP + TILE_SIZE * config.height as i32 + 20;
 
-            field.push(column)
-        }
+        (width as u32, height as u32)
+    }
+
+    pub fn new(board: &engine::Board) -> Self {
+        let field = (0..board.width())
+            .map(|x| (0..board.height()).map(|y| Point::new(Board::index_to_coordinates(x, y))).collect())
+            .collect();
 
         Board {
             field,
-            segments: LinkedList::new(),
-            closed_path: false,
-            current_color: None,
+            pointer: None,
+            flashes: Vec::new(),
+            popups: Vec::new(),
+            hint: Vec::new(),
+            pulse: Duration::from_secs(0),
         }
     }
 
-    pub fn update(&mut self) {
-        self.field.iter_mut().flatten().for_each(|point| {
-            if let Some(point) = point {
-                point.update();
-            }
-        });
+    /// Starts pulsing the dots of `chain`, or stops with `None`.
+    pub fn show_hint(&mut self, chain: Option<&Chain>) {
+        self.hint = chain.map_or_else(Vec::new, |chain| chain.cells().to_vec());
+        self.pulse = Duration::from_secs(0);
     }
 
-    pub fn render(&mut self, canvas: &mut Canvas<Window>) {
-        self.render_board(canvas);
+    pub fn update(&mut self, delta: Duration) {
+        self.field.iter_mut().flatten().for_each(Point::update);
+        self.flashes.iter_mut().for_each(|flash| flash.update(delta));
+        self.flashes.retain(|flash| !flash.is_done());
+        self.popups.iter_mut().for_each(|popup| popup.update(delta));
+        self.popups.retain(|popup| !popup.is_done());
+        self.pulse = Duration::from_nanos(((self.pulse + delta).as_nanos() % HINT_PULSE.as_nanos()) as u64);
+    }
 
-        if let Some(color) = self.current_color {
-            canvas.set_draw_color(color);
-        }
+    pub fn render(&self, canvas: &mut Canvas<Window>, board: &engine::Board) {
+        self.render_board(canvas);
 
-        self.segments.iter().for_each(|segment| {
-            let from = sdlPoint::new(segment.from.x, segment.from.y);
-            let to = sdlPoint::new(segment.to.x, segment.to.y);
-            canvas.draw_line(from, to).unwrap();
+        let chain = board.chain();
 
-            let from = sdlPoint::new(segment.from.x + 1, segment.from.y);
-            let to = sdlPoint::new(segment.to.x + 1, segment.to.y);
-            canvas.draw_line(from, to).unwrap();
+        if let Some(color) = chain.color() {
+            canvas.set_draw_color(dot_color(color));
+        }
 
-            let from = sdlPoint::new(segment.from.x, segment.from.y + 1);
-            let to = sdlPoint::new(segment.to.x, segment.to.y + 1);
-            canvas.draw_line(from, to).unwrap();
+        chain.cells().windows(2).for_each(|pair| {
+            let from = Board::index_to_coordinates(pair[0].x, pair[0].y);
+            let to = Board::index_to_coordinates(pair[1].x, pair[1].y);
+            Board::render_segment(canvas, from, to);
         });
 
+        if let (Some(last), Some(pointer)) = (chain.last(), self.pointer) {
+            if !chain.is_closed() {
+                Board::render_segment(canvas, Board::index_to_coordinates(last.x, last.y), pointer);
+            }
+        }
+
         let texture_creator = canvas.texture_creator();
         let texture = texture_creator.load_texture(Path::new("assets/cats.png")).unwrap();
 
-        self.field.iter_mut().flatten().for_each(|point| {
-            if let Some(point) = point {
-                point.render(canvas, &texture);
+        let grow = pulse_size(self.pulse);
+
+        board.cells().for_each(|cell| {
+            if let Some(color) = board.color(cell) {
+                let grow = if self.hint.contains(&cell) { grow } else { 0 };
+                self.field[cell.x][cell.y].render(canvas, &texture, color, board.is_connected(cell), grow);
+            } else if board.is_wall(cell) {
+                Board::render_wall(canvas, cell);
             }
         });
+
+        self.flashes.iter().for_each(|flash| flash.render(canvas));
+        self.popups.iter().for_each(|popup| popup.render(canvas));
     }
 
-    fn render_board(&mut self, canvas: &mut Canvas<Window>) {
-        let size = (TILE_SIZE * 5) as u32;
+    fn render_board(&self, canvas: &mut Canvas<Window>) {
+        let (width, height) = self.size();
+        let width = (TILE_SIZE * width as i32) as u32;
+        let height = (TILE_SIZE * height as i32) as u32;
         canvas.set_draw_color(Color::RGB(255, 255, 255));
-        canvas.fill_rect(Rect::new(FIELD_OFFSET_LEFT - 4, FIELD_OFFSET_TOP, size + 8, size)).unwrap();
-        canvas.fill_rect(Rect::new(FIELD_OFFSET_LEFT, FIELD_OFFSET_TOP - 4, size, size + 8)).unwrap();
-        canvas.fill_rect(Rect::new(FIELD_OFFSET_LEFT - 2, FIELD_OFFSET_TOP - 2, size + 4, size + 4)).unwrap();
+        canvas.fill_rect(Rect::new(FIELD_OFFSET_LEFT - 4, FIELD_OFFSET_TOP, width + 8, height)).unwrap();
+        canvas.fill_rect(Rect::new(FIELD_OFFSET_LEFT, FIELD_OFFSET_TOP - 4, width, height + 8)).unwrap();
+        canvas.fill_rect(Rect::new(FIELD_OFFSET_LEFT - 2, FIELD_OFFSET_TOP - 2, width + 4, height + 4)).unwrap();
     }
 
-    pub fn handle_click(&mut self, x: i32, y: i32) {
-        if let Some(point) = self.get_point_mut(x, y) {
-            point.connect();
-
-            let center = point.center;
-            let color = point.color;
-
-            self.current_color = Some(color);
-
-            self.segments.push_front(Segment {
-                from: center,
-                to: Coordinates { x, y },
-            });
-
-        }
+    fn size(&self) -> (usize, usize) {
+        (self.field.len(), self.field.first().map_or(0, Vec::len))
     }
 
-    pub fn handle_move(&mut self, x: i32, y: i32) {
-        if self.segments.len() == 0 {
-            return;
-        }
-
-        if self.closed_path {
-            let mut segment = self.segments.front_mut().unwrap();
-            segment.to = segment.from;
-        }
-
-        if let Some(segment) = self.segments.front_mut() {
-            if !self.closed_path {
-                segment.to.x = x;
-                segment.to.y = y;
-            }
-        }
-
-        let point = self.get_point(x, y);
-
-        if None == point {
-            return;
-        }
-
-        let point = point.unwrap();
-        let center = point.center;
-
-        let segment = self.segments.front().unwrap();
-        let prev_point = self.get_point(segment.from.x, segment.from.y).unwrap();
-        let prev_point_center = prev_point.center;
-
-        let action = self.resolve_action(prev_point, point);
-
-        match action {
-            Action::Connect => self.connect(center),
-            Action::Disconnect => self.disconnect(prev_point_center),
-            Action::None => (),
-        };
+    fn render_wall(canvas: &mut Canvas<Window>, cell: Cell) {
+        let center = Board::index_to_coordinates(cell.x, cell.y);
+        let size = TILE_SIZE - 8;
+        canvas.set_draw_color(WALL);
+        canvas.fill_rect(Rect::new(center.x - size / 2, center.y - size / 2, size as u32, size as u32)).unwrap();
     }
 
-    pub fn handle_raise(&mut self) -> i32 {
-        if self.segments.len() < 2 {
-            self.segments.clear();
-
-            self.field.iter_mut().flatten().for_each(|point| {
-                if let Some(point) = point {
-                    point.disconnect();
-                }
-            });
-
-            return 0;
+    fn render_segment(canvas: &mut Canvas<Window>, from: Coordinates, to: Coordinates) {
+        for (dx, dy) in &[(0, 0), (1, 0), (0, 1)] {
+            let from = sdlPoint::new(from.x + dx, from.y + dy);
+            let to = sdlPoint::new(to.x + dx, to.y + dy);
+            canvas.draw_line(from, to).unwrap();
         }
-
-        self.segments.clear();
-
-        let count = self.clear_points();
-
-        self.closed_path = false;
-        self.current_color = None;
-
-        count
     }
 
-    fn clear_points(&mut self) -> i32 {
-        let mut count = 0;
-
-        self.field = self.field.iter_mut().enumerate().map(|(x, column)| {
-            let mut new_column: Vec<Option<Point>> = vec![None; 5];
-
-            let points = column.iter_mut().filter(|point| !point.unwrap().connected);
-
-            points.rev().enumerate()
-                .for_each(|(y, point)| {
-                    let mut point = point.unwrap();
-                    let coords = Board::index_to_coordinates(x,  4 - y);
-                    point.move_to(coords);
-                    new_column[4 - y] = Some(point);
-                });
-
-            for i in 0..5 {
-                if let None = new_column[i] {
-                    let coords = Board::index_to_coordinates(x,  i);
-                    new_column[i] = Some(Point::new(coords));
-                    count += 1;
-                }
-            }
-
-            new_column
-        }).collect();
-
-        count
-    }
-
-    fn get_point(&self, x: i32, y: i32) -> Option<&Point> {
-        if let Some((x, y)) = Board::coordinates_to_index(Coordinates { x, y }) {
-            let column = self.field.get(x).unwrap();
-            if let Some(point) = column.get(y).unwrap() {
-                return Some(point);
-            }
-        }
-
-        None
+    /// Remembers where the pointer is so the open end of the chain can follow it.
+    pub fn point_to(&mut self, x: i32, y: i32) {
+        self.pointer = Some(Coordinates { x, y });
     }
 
-    fn get_point_mut(&mut self, x: i32, y: i32) -> Option<&mut Point> {
-        if let Some((x, y)) = Board::coordinates_to_index(Coordinates { x, y }) {
-            let column = self.field.get_mut(x).unwrap();
-            if let Some(point) = column.get_mut(y).unwrap() {
-                return Some(point);
-            }
-        }
-
-        None
+    pub fn release(&mut self) {
+        self.pointer = None;
     }
 
-    fn resolve_action(&self, prev: &Point, next: &Point) -> Action {
-        if prev.center == next.center {
-            return Action::None;
-        }
-
-        if prev.color != next.color {
-            return Action::None;
-        }
-
-        if Board::is_siblings(prev, next) && !self.is_previous(next) {
-            return Action::Connect;
-        }
-
-        if Board::is_siblings(prev, next) && self.is_previous(next) {
-            return Action::Disconnect;
-        }
-
-        Action::None
+    pub fn cell_at(&self, x: i32, y: i32) -> Option<Cell> {
+        self.coordinates_to_index(Coordinates { x, y }).map(|(x, y)| Cell::new(x, y))
     }
 
-    fn connect(&mut self, next: Coordinates) {
-        if self.closed_path {
-            return;
-        }
-
-        let segment = self.segments.front_mut().unwrap();
-        segment.to.x = next.x;
-        segment.to.y = next.y;
+    /// Moves the sprites after a chain was cleared: survivors slide down, new dots drop in from above,
+    /// and if the board had to be shuffled every dot makes its way to its new cell.
+    pub fn apply(&mut self, clear: &Clear) {
+        let previous = self.field.clone();
 
-        self.segments.push_front(Segment {
-            from: next,
-            to: next,
+        clear.falls.iter().for_each(|fall| {
+            let mut point = previous[fall.from.x][fall.from.y];
+            point.move_to(Board::index_to_coordinates(fall.to.x, fall.to.y));
+            self.field[fall.to.x][fall.to.y] = point;
         });
 
-        let point = self.get_point(next.x, next.y).unwrap();
-
-        if point.connected {
-            let color = point.color;
-            self.connect_all(color);
-
-            self.closed_path = true;
-        }
-
-        let point = self.get_point_mut(next.x, next.y).unwrap();
-
-        point.connect();
-    }
-
-    fn disconnect(&mut self, next: Coordinates) {
-        if self.segments.len() < 2 {
-            return;
-        }
-
-        let from;
-        let to;
-
-        let front = self.segments.pop_front();
-        to = front.unwrap().to;
-        let front = self.segments.pop_front();
-        from = front.unwrap().from;
-
-        self.segments.push_front(Segment {
-            from,
-            to,
+        clear.spawned.iter().for_each(|cell| {
+            self.field[cell.x][cell.y] = Point::new(Board::index_to_coordinates(cell.x, cell.y));
         });
 
+        let fallen = self.field.clone();
+        clear.shuffled.iter().for_each(|shuffle| {
+            let mut point = fallen[shuffle.from.x][shuffle.from.y];
+            point.slide_to(Board::index_to_coordinates(shuffle.to.x, shuffle.to.y));
+            self.field[shuffle.to.x][shuffle.to.y] = point;
+        });
 
-        if self.closed_path {
-            let color = self.get_point(next.x, next.y).unwrap().color;
-            self.disconnect_all(color);
-
-            self.closed_path = false;
-        } else {
-            let point = self.get_point_mut(next.x, next.y).unwrap();
-            point.disconnect();
+        if clear.closed {
+            let (width, height) = self.size();
+            let area = Board::area(Cell::new(0, 0), Cell::new(width - 1, height - 1));
+            self.flashes.push(Flash::new(area, dot_color(clear.color), LOOP_FLASH));
         }
-    }
 
-    fn connect_all(&mut self, color: Color) {
-        self.field.iter_mut().flatten().for_each(|point| {
-            if let Some(point) = point {
-                if point.color == color {
-                    point.connect();
-                }
-            }
+        clear.exploded.iter().for_each(|bomb| {
+            let (width, height) = self.size();
+            let from = Cell::new(bomb.x.saturating_sub(BLAST_RADIUS), bomb.y.saturating_sub(BLAST_RADIUS));
+            let to = Cell::new((bomb.x + BLAST_RADIUS).min(width - 1), (bomb.y + BLAST_RADIUS).min(height - 1));
+            self.flashes.push(Flash::new(Board::area(from, to), Color::RGB(255, 255, 255), BOMB_FLASH));
         });
     }
 
-    fn disconnect_all(&mut self, color: Color) {
-        let connected_by_segments: Vec<Point> = self.field.iter().flatten()
-            .filter(|point| {
-                self.segments.iter().any(|segment| segment.from == point.unwrap().center)
-            })
-            .map(|p| {
-                p.unwrap()
-            })
-            .collect();
+    /// Outlines the cell the keyboard cursor is on.
+    pub fn render_cursor(&self, canvas: &mut Canvas<Window>, cell: Cell, color: Color) {
+        let area = Board::area(cell, cell);
+        canvas.set_draw_color(color);
 
-        self.field.iter_mut().flatten().for_each(|point| {
-            if let Some(point) = point {
-                if point.color == color && !connected_by_segments.contains(point) {
-                    point.disconnect();
-                }
-            }
+        (0..CURSOR_WIDTH as i32).for_each(|inset| {
+            let rect = Rect::new(area.x() + inset, area.y() + inset, area.width() - inset as u32 * 2, area.height() - inset as u32 * 2);
+            canvas.draw_rect(rect).unwrap();
         });
     }
 
-    fn is_previous(&self, point: &Point) -> bool {
-        let second_index;
-        if self.segments.len() < 2 {
-            second_index = 0
-        } else {
-            second_index = 1;
-        }
-
-        let from = self.segments.iter().nth(second_index).unwrap();
-
-        if from.from == point.center {
-            return true;
-        }
+    /// Floats the points of a turn up from `cell`, one line per part, the total on top.
+    pub fn show_score(&mut self, breakdown: &Breakdown, cell: Cell, color: Color) {
+        let center = Board::index_to_coordinates(cell.x, cell.y);
+        let mut lines: Vec<String> = breakdown.parts.iter()
+            .filter(|part| part.bonus != Bonus::Dots)
+            .map(|part| format!("{} +{}", bonus_label(part.bonus), number::abbreviated(part.points)))
+            .collect();
+        lines.push(format!("+{}", number::abbreviated(breakdown.total())));
 
-        false
+        lines.into_iter().enumerate().for_each(|(index, text)| {
+            let at = Coordinates { x: center.x, y: center.y - 8 - index as i32 * POPUP_SPACING };
+            self.popups.push(Popup::new(text, at, color, POPUP_TIME));
+        });
     }
 
-    fn is_siblings(p1: &Point, p2: &Point) -> bool {
-        let (x1, y1) = Board::coordinates_to_index(p1.center).unwrap();
-        let (x2, y2) = Board::coordinates_to_index(p2.center).unwrap();
-
-        if x1 == x2 && (y1 as i32 - y2 as i32).abs() == 1 {
-            return true;
-        }
-
-        if y1 == y2 && (x1 as i32 - x2 as i32).abs() == 1 {
-            return true;
-        }
+    /// Screen rectangle covering the cells between two corners.
+    fn area(from: Cell, to: Cell) -> Rect {
+        let top_left = Board::index_to_coordinates(from.x, from.y);
+        let width = (to.x - from.x + 1) as i32 * TILE_SIZE;
+        let height = (to.y - from.y + 1) as i32 * TILE_SIZE;
 
-        false
+        Rect::new(top_left.x - TILE_SIZE / 2, top_left.y - TILE_SIZE / 2, width as u32, height as u32)
     }
 
     fn index_to_coordinates(x: usize, y: usize) -> Coordinates {
@@ -377,11 +239,12 @@ impl Board {
         Coordinates { x, y }
     }
 
-    fn coordinates_to_index(coordinates: Coordinates) -> Option<(usize, usize)> {
+    fn coordinates_to_index(&self, coordinates: Coordinates) -> Option<(usize, usize)> {
+        let (width, height) = self.size();
         let x = ((coordinates.x - FIELD_OFFSET_LEFT) as f32 / TILE_SIZE as f32).floor() as i32;
         let y = ((coordinates.y - FIELD_OFFSET_TOP) as f32 / TILE_SIZE as f32).floor() as i32;
 
-        if x < 0 || y < 0 || x > 4 || y > 4 {
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
             return None;
         }
 
@@ -389,6 +252,21 @@ impl Board {
     }
 }
 
+/// Pixels a hinted dot grows by, `pulse` into its beat: up and back down along a sine.
+fn pulse_size(pulse: Duration) -> i32 {
+    let phase = pulse.as_secs_f32() / HINT_PULSE.as_secs_f32();
+
+    (HINT_GROW * (phase * std::f32::consts::PI).sin()).round() as i32
+}
+
+fn bonus_label(bonus: Bonus) -> &'static str {
+    match bonus {
+        Bonus::Dots => "DOTS",
+        Bonus::Length => "LONG",
+        Bonus::Loop => "LOOP",
+        Bonus::Combo => "COMBO",
+    }
+}
 
 #[cfg(test)]
 mod test {
@@ -409,32 +287,67 @@ mod test {
         assert_eq!(p2, Coordinates { x: 320, y: 370 });
     }
 
+    fn board(width: usize, height: usize) -> Board {
+        Board::new(&engine::Board::new(&Config { width, height, ..Config::default() }, 0))
+    }
+
+    #[test]
+    fn hinted_dots_pulse() {
+        assert_eq!(pulse_size(Duration::from_secs(0)), 0);
+        assert_eq!(pulse_size(HINT_PULSE / 2), HINT_GROW as i32);
+        assert!(pulse_size(HINT_PULSE / 4) > 0);
+        assert!(pulse_size(HINT_PULSE / 4) < HINT_GROW as i32);
+    }
+
+    #[test]
+    fn window_size() {
+        assert_eq!(Board::window_size(&Config::default()), (400, 420));
+        assert_eq!(Board::window_size(&Config { width: 7, height: 9, ..Config::default() }), (520, 660));
+    }
+
     #[test]
     fn coordinates_to_index() {
-        let p1 = Board::coordinates_to_index(Coordinates { x: 80, y: 130 }).unwrap();
+        let board = board(5, 5);
+
+        let p1 = board.coordinates_to_index(Coordinates { x: 80, y: 130 }).unwrap();
         assert_eq!(p1 , (0, 0));
 
         let c2 = prepare_coordinates(3, 4, 10);
-        let p2 = Board::coordinates_to_index(c2).unwrap();
+        let p2 = board.coordinates_to_index(c2).unwrap();
         assert_eq!(p2, (3, 4));
 
         let c3 = prepare_coordinates(4, 3, 10);
-        let p3 = Board::coordinates_to_index(c3).unwrap();
+        let p3 = board.coordinates_to_index(c3).unwrap();
         assert_eq!(p3, (4, 3));
 
         let c4 = prepare_coordinates(0, 0, 10);
-        let p4 = Board::coordinates_to_index(c4).unwrap();
+        let p4 = board.coordinates_to_index(c4).unwrap();
         assert_eq!(p4, (0, 0));
 
         let c5 = prepare_coordinates(7, 3, 10);
-        let p5 = Board::coordinates_to_index(c5);
+        let p5 = board.coordinates_to_index(c5);
         assert_eq!(p5, None);
 
-        let c6 = prepare_coordinates(2, 3, 10);
-        let p6 = Board::coordinates_to_index(c6);
+        let c6 = prepare_coordinates(2, 5, 10);
+        let p6 = board.coordinates_to_index(c6);
         assert_eq!(p6, None);
     }
 
+    #[test]
+    fn coordinates_to_index_on_non_square_board() {
+        let board = board(7, 9);
+
+        assert_eq!(board.coordinates_to_index(prepare_coordinates(6, 8, 10)), Some((6, 8)));
+        assert_eq!(board.coordinates_to_index(prepare_coordinates(7, 3, 10)), None);
+        assert_eq!(board.coordinates_to_index(prepare_coordinates(3, 9, 10)), None);
+    }
+
+    #[test]
+    fn area_covers_cells() {
+        assert_eq!(Board::area(Cell::new(0, 0), Cell::new(4, 4)), Rect::new(50, 100, 300, 300));
+        assert_eq!(Board::area(Cell::new(1, 2), Cell::new(1, 2)), Rect::new(110, 220, 60, 60));
+    }
+
     fn prepare_coordinates(x: usize, y: usize, error: i32) -> Coordinates {
         let c = Board::index_to_coordinates(x, y);
         Coordinates {
@@ -1,9 +1,28 @@
 use sdl2::pixels::Color;
 
+use cats::engine::Color as DotColor;
+
 pub const PURPLE: Color = Color { r: 255, g: 97, b: 178, a: 0xff };
 pub const BLUE: Color = Color { r: 73, g: 139, b: 255, a: 0xff };
 pub const GREEN: Color = Color { r: 175, g: 255, b: 73, a: 0xff };
 pub const RED: Color = Color { r: 255, g: 15, b: 30, a: 0xff };
 pub const CYAN: Color = Color { r: 73, g: 255, b: 250, a: 0xff };
+pub const ORANGE: Color = Color { r: 255, g: 150, b: 40, a: 0xff };
+pub const BROWN: Color = Color { r: 150, g: 90, b: 50, a: 0xff };
+pub const GRAY: Color = Color { r: 130, g: 130, b: 130, a: 0xff };
 
 pub const BACKGROUND: Color = Color { r:255, g: 250, b: 240, a: 0xff };
+pub const WALL: Color = Color { r: 225, g: 218, b: 205, a: 0xff };
+
+pub fn dot_color(color: DotColor) -> Color {
+    match color {
+        DotColor::Purple => PURPLE,
+        DotColor::Blue => BLUE,
+        DotColor::Green => GREEN,
+        DotColor::Red => RED,
+        DotColor::Cyan => CYAN,
+        DotColor::Orange => ORANGE,
+        DotColor::Brown => BROWN,
+        DotColor::Gray => GRAY,
+    }
+}
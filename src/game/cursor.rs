@@ -0,0 +1,50 @@
+use cats::engine::Cell;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The middle of a board of `width` by `height`, where the cursor shows up.
+pub fn centered(width: usize, height: usize) -> Cell {
+    Cell::new(width.saturating_sub(1) / 2, height.saturating_sub(1) / 2)
+}
+
+/// The cell next to `cell` towards `direction`, `cell` itself at the edge of the board.
+pub fn step(cell: Cell, direction: Direction, width: usize, height: usize) -> Cell {
+    let Cell { x, y } = cell;
+
+    match direction {
+        Direction::Up => Cell::new(x, y.saturating_sub(1)),
+        Direction::Down => Cell::new(x, (y + 1).min(height.saturating_sub(1))),
+        Direction::Left => Cell::new(x.saturating_sub(1), y),
+        Direction::Right => Cell::new((x + 1).min(width.saturating_sub(1)), y),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn starts_in_the_middle() {
+        assert_eq!(centered(5, 5), Cell::new(2, 2));
+        assert_eq!(centered(4, 7), Cell::new(1, 3));
+    }
+
+    #[test]
+    fn steps_stay_on_the_board() {
+        let corner = Cell::new(0, 0);
+        assert_eq!(step(corner, Direction::Up, 3, 2), Cell::new(0, 0));
+        assert_eq!(step(corner, Direction::Left, 3, 2), Cell::new(0, 0));
+        assert_eq!(step(corner, Direction::Right, 3, 2), Cell::new(1, 0));
+        assert_eq!(step(corner, Direction::Down, 3, 2), Cell::new(0, 1));
+
+        let corner = Cell::new(2, 1);
+        assert_eq!(step(corner, Direction::Right, 3, 2), Cell::new(2, 1));
+        assert_eq!(step(corner, Direction::Down, 3, 2), Cell::new(2, 1));
+    }
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use crate::game::text::render_text_centered;
+use crate::game::Coordinates;
+
+const FLASH_ALPHA: u8 = 0xa0;
+/// How far a popup drifts up while it fades.
+const POPUP_RISE: f32 = 40.0;
+
+/// Colored rectangle that fades out, drawn over the board when a loop or a bomb goes off.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Flash {
+    area: Rect,
+    color: Color,
+    length: Duration,
+    left: Duration,
+}
+
+impl Flash {
+    pub fn new(area: Rect, color: Color, length: Duration) -> Self {
+        Flash {
+            area,
+            color,
+            length,
+            left: length,
+        }
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        self.left = self.left.checked_sub(delta).unwrap_or_default();
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.left == Duration::from_secs(0)
+    }
+
+    fn alpha(&self) -> u8 {
+        (FLASH_ALPHA as f32 * self.left.as_secs_f32() / self.length.as_secs_f32()) as u8
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>) {
+        canvas.set_draw_color(Color::RGBA(self.color.r, self.color.g, self.color.b, self.alpha()));
+        canvas.fill_rect(self.area).unwrap();
+    }
+}
+
+/// Floating text such as "+12" that drifts up from where points were scored.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Popup {
+    text: String,
+    at: Coordinates,
+    color: Color,
+    length: Duration,
+    left: Duration,
+}
+
+impl Popup {
+    pub fn new(text: String, at: Coordinates, color: Color, length: Duration) -> Self {
+        Popup {
+            text,
+            at,
+            color,
+            length,
+            left: length,
+        }
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        self.left = self.left.checked_sub(delta).unwrap_or_default();
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.left == Duration::from_secs(0)
+    }
+
+    fn progress(&self) -> f32 {
+        1.0 - self.left.as_secs_f32() / self.length.as_secs_f32()
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>) {
+        let alpha = (255.0 * (1.0 - self.progress())) as u8;
+        let color = Color::RGBA(self.color.r, self.color.g, self.color.b, alpha);
+        let y = self.at.y - (POPUP_RISE * self.progress()) as i32;
+
+        render_text_centered(canvas, &self.text, self.at.x, y, 2, color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fades_out() {
+        let mut flash = Flash::new(Rect::new(0, 0, 10, 10), Color::RGB(255, 0, 0), Duration::from_millis(400));
+        assert_eq!(flash.alpha(), FLASH_ALPHA);
+
+        flash.update(Duration::from_millis(200));
+        assert_eq!(flash.alpha(), FLASH_ALPHA / 2);
+        assert!(!flash.is_done());
+
+        flash.update(Duration::from_millis(300));
+        assert_eq!(flash.alpha(), 0);
+        assert!(flash.is_done());
+    }
+
+    #[test]
+    fn popup_rises_until_done() {
+        let mut popup = Popup::new("+3".to_string(), Coordinates { x: 0, y: 0 }, Color::RGB(0, 0, 0), Duration::from_secs(1));
+        assert_eq!(popup.progress(), 0.0);
+
+        popup.update(Duration::from_millis(500));
+        assert_eq!(popup.progress(), 0.5);
+
+        popup.update(Duration::from_secs(1));
+        assert!(popup.is_done());
+    }
+}
@@ -0,0 +1,178 @@
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::rect::Rect;
+
+use crate::game::color::BACKGROUND;
+use crate::game::text::render_text_centered;
+use crate::game::{Coordinates, OVERLAY, TEXT_COLOR};
+
+const BUTTON_WIDTH: u32 = 200;
+const BUTTON_HEIGHT: u32 = 36;
+const BUTTON_GAP: i32 = 12;
+/// Space between the title and the first button.
+const TITLE_GAP: i32 = 48;
+
+/// Moving around a menu, from whichever device.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Navigation {
+    Up,
+    Down,
+    /// Picks the focused button.
+    Choose,
+    /// Leaves the menu.
+    Back,
+    /// The mouse moved, focusing the button under it.
+    Hover(Coordinates),
+    /// Picks the button clicked or tapped.
+    Click(Coordinates),
+    Quit,
+}
+
+/// A column of buttons drawn over the game, one of them focused.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Menu<T> {
+    title: String,
+    items: Vec<(String, T)>,
+    focus: usize,
+}
+
+impl<T: Copy> Menu<T> {
+    /// A menu with the first of `items` focused, each a label and what it stands for.
+    pub fn new(title: &str, items: Vec<(String, T)>) -> Self {
+        Menu { title: title.to_string(), items, focus: 0 }
+    }
+
+    pub fn focused(&self) -> Option<T> {
+        self.items.get(self.focus).map(|&(_, item)| item)
+    }
+
+    /// Renames the focused button.
+    pub fn relabel(&mut self, label: String) {
+        if let Some(item) = self.items.get_mut(self.focus) {
+            item.0 = label;
+        }
+    }
+
+    /// Moves the focus, returns the item when one is picked. Leaving is up to the caller.
+    pub fn navigate(&mut self, navigation: Navigation, size: (u32, u32)) -> Option<T> {
+        let count = self.items.len();
+        if count == 0 {
+            return None;
+        }
+
+        match navigation {
+            Navigation::Up => self.focus = (self.focus + count - 1) % count,
+            Navigation::Down => self.focus = (self.focus + 1) % count,
+            Navigation::Choose => return self.focused(),
+            Navigation::Hover(point) => {
+                if let Some(index) = self.item_at(point, size) {
+                    self.focus = index;
+                }
+            },
+            Navigation::Click(point) => {
+                let index = self.item_at(point, size)?;
+                self.focus = index;
+                return self.focused();
+            },
+            Navigation::Back | Navigation::Quit => (),
+        }
+
+        None
+    }
+
+    /// Where the title goes in a window of `size`, the buttons are below it.
+    pub fn title_y(&self, size: (u32, u32)) -> i32 {
+        button_area(0, self.items.len(), size).y() - TITLE_GAP
+    }
+
+    fn item_at(&self, point: Coordinates, size: (u32, u32)) -> Option<usize> {
+        (0..self.items.len()).find(|&index| button_area(index, self.items.len(), size).contains_point((point.x, point.y)))
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>) {
+        let size = canvas.output_size().unwrap();
+        let center = size.0 as i32 / 2;
+
+        canvas.set_draw_color(OVERLAY);
+        canvas.fill_rect(Rect::new(0, 0, size.0, size.1)).unwrap();
+
+        render_text_centered(canvas, &self.title, center, self.title_y(size), 3, TEXT_COLOR);
+
+        self.items.iter().enumerate().for_each(|(index, (label, _))| {
+            let area = button_area(index, self.items.len(), size);
+            let color = if index == self.focus {
+                canvas.set_draw_color(TEXT_COLOR);
+                canvas.fill_rect(area).unwrap();
+                BACKGROUND
+            } else {
+                canvas.set_draw_color(TEXT_COLOR);
+                canvas.draw_rect(area).unwrap();
+                TEXT_COLOR
+            };
+
+            render_text_centered(canvas, label, center, area.y() + (BUTTON_HEIGHT as i32 - 14) / 2, 2, color);
+        });
+    }
+}
+
+/// Where button `index` of `count` goes in a window of `size`: a column in the middle,
+/// narrower on small windows.
+fn button_area(index: usize, count: usize, size: (u32, u32)) -> Rect {
+    let width = BUTTON_WIDTH.min(size.0.saturating_sub(20));
+    let column = count as i32 * (BUTTON_HEIGHT as i32 + BUTTON_GAP) - BUTTON_GAP;
+    let top = (size.1 as i32 - column + TITLE_GAP) / 2;
+
+    Rect::new(
+        (size.0 as i32 - width as i32) / 2,
+        top + index as i32 * (BUTTON_HEIGHT as i32 + BUTTON_GAP),
+        width,
+        BUTTON_HEIGHT
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn menu() -> Menu<char> {
+        Menu::new("MENU", vec![("A".to_string(), 'a'), ("B".to_string(), 'b'), ("C".to_string(), 'c')])
+    }
+
+    #[test]
+    fn focus_wraps_around() {
+        let mut menu = menu();
+
+        assert_eq!(menu.navigate(Navigation::Up, (400, 400)), None);
+        assert_eq!(menu.focused(), Some('c'));
+        menu.navigate(Navigation::Down, (400, 400));
+        menu.navigate(Navigation::Down, (400, 400));
+        assert_eq!(menu.navigate(Navigation::Choose, (400, 400)), Some('b'));
+        assert_eq!(menu.navigate(Navigation::Back, (400, 400)), None);
+    }
+
+    #[test]
+    fn the_mouse_focuses_and_picks_buttons() {
+        let mut menu = menu();
+        let size = (400, 400);
+        let third = button_area(2, 3, size);
+        let point = Coordinates { x: third.x() + 5, y: third.y() + 5 };
+
+        assert_eq!(menu.navigate(Navigation::Hover(point), size), None);
+        assert_eq!(menu.focused(), Some('c'));
+        assert_eq!(menu.navigate(Navigation::Hover(Coordinates { x: 0, y: 0 }), size), None);
+        assert_eq!(menu.focused(), Some('c'));
+        assert_eq!(menu.navigate(Navigation::Click(Coordinates { x: 0, y: 0 }), size), None);
+        assert_eq!(menu.navigate(Navigation::Click(point), size), Some('c'));
+    }
+
+    #[test]
+    fn buttons_stack_in_the_middle() {
+        let first = button_area(0, 2, (400, 300));
+        let second = button_area(1, 2, (400, 300));
+
+        assert_eq!((first.x(), first.width()), (100, BUTTON_WIDTH));
+        assert_eq!(second.y() - first.y(), BUTTON_HEIGHT as i32 + BUTTON_GAP);
+        assert!(first.y() - TITLE_GAP >= 0 && second.bottom() <= 300);
+        assert_eq!(button_area(0, 1, (120, 300)).width(), 100);
+    }
+}
@@ -0,0 +1,77 @@
+/// Suffixes for thousands, millions and so on, enough for any `u64`.
+const UNITS: [(u64, &str); 6] = [
+    (1_000_000_000_000_000_000, "QI"),
+    (1_000_000_000_000_000, "QA"),
+    (1_000_000_000_000, "T"),
+    (1_000_000_000, "B"),
+    (1_000_000, "M"),
+    (1_000, "K"),
+];
+
+/// `1234567` as `1,234,567`.
+pub fn separated(value: u64) -> String {
+    let digits = value.to_string();
+    let mut text = String::new();
+
+    digits.chars().enumerate().for_each(|(index, digit)| {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            text.push(',');
+        }
+        text.push(digit);
+    });
+
+    text
+}
+
+/// `1234567` as `1.2M`: at most three digits and a suffix, truncated rather than rounded
+/// so the shown value is never more than was scored.
+pub fn abbreviated(value: u64) -> String {
+    match UNITS.iter().find(|(unit, _)| value >= *unit) {
+        Some(&(unit, suffix)) if value / unit < 100 => format!("{}.{}{}", value / unit, value % unit * 10 / unit, suffix),
+        Some(&(unit, suffix)) => format!("{}{}", value / unit, suffix),
+        None => value.to_string(),
+    }
+}
+
+/// The most precise form of `value` that `measure` says fits in `width` pixels.
+pub fn fitting<F: Fn(&str) -> i32>(value: u64, width: i32, measure: F) -> String {
+    let text = separated(value);
+
+    if measure(&text) <= width {
+        return text;
+    }
+
+    abbreviated(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn separates_thousands() {
+        assert_eq!(separated(0), "0");
+        assert_eq!(separated(999), "999");
+        assert_eq!(separated(1000), "1,000");
+        assert_eq!(separated(123_456), "123,456");
+        assert_eq!(separated(u64::MAX), "18,446,744,073,709,551,615");
+    }
+
+    #[test]
+    fn abbreviates() {
+        assert_eq!(abbreviated(999), "999");
+        assert_eq!(abbreviated(1_000), "1.0K");
+        assert_eq!(abbreviated(12_345), "12.3K");
+        assert_eq!(abbreviated(999_999), "999K");
+        assert_eq!(abbreviated(1_250_000), "1.2M");
+        assert_eq!(abbreviated(u64::MAX), "18.4QI");
+    }
+
+    #[test]
+    fn fits_the_width() {
+        let measure = |text: &str| text.len() as i32 * 10;
+
+        assert_eq!(fitting(123_456, 70, measure), "123,456");
+        assert_eq!(fitting(1_234_567, 70, measure), "1.2M");
+    }
+}
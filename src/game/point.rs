@@ -1,40 +1,42 @@
-use sdl2::pixels::Color;
 use sdl2::render::{Canvas, Texture};
 use sdl2::video::Window;
 use sdl2::rect::Rect;
 
-use rand::Rng;
+use cats::engine::Color;
 
-use crate::game::color::{PURPLE, BLUE, GREEN, RED, CYAN};
 use crate::game::Coordinates;
 
 const VELOCITY: i32 = 2;
+const DROP_HEIGHT: i32 = 400;
+/// Pixels a shuffled dot moves per frame.
+const SLIDE_SPEED: i32 = 8;
 
-#[derive(PartialEq, Copy, Clone)]
+/// Sprite of a single dot, animates the fall towards its place on the board.
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub struct Point {
-    pub color: Color,
     pub center: Coordinates,
-    pub connected: bool,
-    texture_offset: i32,
     transition_center: Coordinates,
     speed: i32,
+    /// Moving straight to its place after a shuffle instead of falling.
+    sliding: bool,
 }
 
 impl Point {
     pub fn new(coordinates: Coordinates) -> Self {
-        let (color, offset) = Point::random_color();
-
         Point {
-            color,
-            texture_offset: offset as i32,
             center: coordinates,
-            transition_center: Coordinates { x: coordinates.x, y: coordinates.y - 400 },
-            connected: false,
+            transition_center: Coordinates { x: coordinates.x, y: coordinates.y - DROP_HEIGHT },
             speed: 0,
+            sliding: false,
         }
     }
 
     pub fn update(&mut self) {
+        if self.sliding {
+            self.slide();
+            return;
+        }
+
         let step = self.speed;
         let diff = self.center.y - self.transition_center.y;
 
@@ -50,66 +52,54 @@ impl Point {
         }
     }
 
-    pub fn render<'a>(&mut self, canvas: &mut Canvas<Window>, texture: &'a Texture) {
+    /// Draws the sprite `grow` pixels larger than it is on each side, for pulsing it.
+    pub fn render(&self, canvas: &mut Canvas<Window>, texture: &Texture, color: Color, connected: bool, grow: i32) {
         let center = self.transition_center;
         let texture_size = 32;
-
-        if self.connected {
-            let src = Rect::new(
-                self.texture_offset * texture_size,
-                texture_size,
-                texture_size as u32,
-                texture_size as u32
-            );
-            let target = Rect::new(
-                center.x - texture_size / 2,
-                center.y - texture_size / 2,
-                texture_size as u32,
-                texture_size as u32
-            );
-            canvas.copy(&texture, src, target).unwrap();
-        } else {
-            let src = Rect::new(
-                self.texture_offset * texture_size,
-                0,
-                texture_size as u32,
-                texture_size as u32
-            );
-            let target = Rect::new(
-                center.x - texture_size / 2,
-                center.y - texture_size / 2,
-                texture_size as u32,
-                texture_size as u32
-            );
-            canvas.copy(&texture, src, target).unwrap();
-        }
+        let row = if connected { texture_size } else { 0 };
+        let size = texture_size + grow * 2;
+
+        let src = Rect::new(
+            color.index() as i32 * texture_size,
+            row,
+            texture_size as u32,
+            texture_size as u32
+        );
+        let target = Rect::new(
+            center.x - size / 2,
+            center.y - size / 2,
+            size as u32,
+            size as u32
+        );
+        canvas.copy(texture, src, target).unwrap();
     }
 
-    pub fn move_to(&mut self, coordinates: Coordinates) {
-        if coordinates == self.center {
+    fn slide(&mut self) {
+        let (dx, dy) = (self.center.x - self.transition_center.x, self.center.y - self.transition_center.y);
+        let distance = dx.abs().max(dy.abs());
+
+        if distance <= SLIDE_SPEED {
+            self.transition_center = self.center;
+            self.sliding = false;
             return;
         }
 
-        self.center.y = coordinates.y;
+        self.transition_center.x += dx * SLIDE_SPEED / distance;
+        self.transition_center.y += dy * SLIDE_SPEED / distance;
     }
 
-    pub fn connect(&mut self) {
-        self.connected = true;
+    /// Moves the dot to another cell in a straight line, from wherever it is drawn now.
+    pub fn slide_to(&mut self, coordinates: Coordinates) {
+        self.center = coordinates;
+        self.sliding = self.transition_center != coordinates;
+        self.speed = 0;
     }
 
-    pub fn disconnect(&mut self) {
-        self.connected = false;
-    }
-
-    fn random_color() -> (Color, usize) {
-        let colors = [PURPLE, BLUE, GREEN, RED, CYAN];
-
-        let mut rng = rand::thread_rng();
-
-        let offset = rng.gen_range(0, 5);
-
-        let color = colors[offset];
+    pub fn move_to(&mut self, coordinates: Coordinates) {
+        if coordinates == self.center {
+            return;
+        }
 
-        (color, offset)
+        self.center.y = coordinates.y;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,76 @@
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::rect::Rect;
+
+use cats::scores::{Entry, Table};
+
+use crate::game::number;
+use crate::game::text::{render_text, render_text_centered, text_width};
+use crate::game::{OVERLAY, TEXT_COLOR};
+
+/// Characters in the score column of a row.
+const SCORE_COLUMN: usize = 10;
+const ROW_HEIGHT: i32 = 24;
+
+/// Draws one high score table over the whole window, `index` of `count` tables.
+pub fn render_table(canvas: &mut Canvas<Window>, table: Option<&Table>, index: usize, count: usize) {
+    let (width, height) = canvas.output_size().unwrap();
+    let (width, height) = (width as i32, height as i32);
+    let center = width / 2;
+
+    canvas.set_draw_color(OVERLAY);
+    canvas.fill_rect(Rect::new(0, 0, width as u32, height as u32)).unwrap();
+
+    render_text_centered(canvas, "HIGH SCORES", center, 20, 3, TEXT_COLOR);
+
+    let table = match table {
+        Some(table) => table,
+        None => {
+            render_text_centered(canvas, "NO GAMES YET", center, height / 2, 2, TEXT_COLOR);
+            render_text_centered(canvas, "H CLOSE", center, height - 30, 1, TEXT_COLOR);
+            return;
+        },
+    };
+
+    let title = table.key.to_string();
+    let scale = fitting_scale(&title, width - 20);
+    render_text_centered(canvas, &title, center, 56, scale, TEXT_COLOR);
+
+    let rows: Vec<String> = table.entries.iter().enumerate().map(|(place, entry)| row(place, entry)).collect();
+    let scale = rows.iter().map(|row| fitting_scale(row, width - 20)).min().unwrap_or(2);
+    let left = center - rows.first().map_or(0, |row| text_width(row, scale)) / 2;
+
+    rows.iter().enumerate().for_each(|(place, row)| {
+        render_text(canvas, row, left, 84 + place as i32 * ROW_HEIGHT, scale, TEXT_COLOR);
+    });
+
+    let hint = format!("< {}/{} >  H CLOSE", index + 1, count);
+    render_text_centered(canvas, &hint, center, height - 30, 1, TEXT_COLOR);
+}
+
+/// `place. score date duration` with the columns lined up.
+fn row(place: usize, entry: &Entry) -> String {
+    let score = number::fitting(entry.score, SCORE_COLUMN as i32, |text| text.len() as i32);
+    let seconds = entry.duration().as_secs();
+
+    format!("{:>2}. {:>width$} {} {:>2}:{:02}", place + 1, score, entry.date(), seconds / 60, seconds % 60, width = SCORE_COLUMN)
+}
+
+/// Largest of scale 2 and 1 at which `text` is at most `width` wide.
+fn fitting_scale(text: &str, width: i32) -> i32 {
+    if text_width(text, 2) <= width { 2 } else { 1 }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rows_line_up() {
+        let entry = Entry { score: 12_345, played_at: 0, duration: 75, moves: 3, seed: 1 };
+        let long = Entry { score: 123_456_789_012, duration: 6000, ..entry.clone() };
+
+        assert_eq!(row(0, &entry), " 1.     12,345 1970-01-01  1:15");
+        assert_eq!(row(9, &long), "10.       123B 1970-01-01 100:00");
+    }
+}
@@ -0,0 +1,231 @@
+//! Turns what comes in from the mouse, keyboard, controllers and touch screens into
+//! commands for the game. Everything done on the board becomes a replay `Action`, so every
+//! device and the replays play the game the same way.
+
+pub(crate) mod bindings;
+pub(crate) mod controllers;
+pub(crate) mod touch;
+
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+
+use cats::replay::Action;
+
+use crate::game::cursor::{self, Direction};
+use crate::game::menu::Navigation;
+use crate::game::{Coordinates, Game};
+use bindings::{Bindings, Control};
+use controllers::Controllers;
+use touch::{Touch, TOUCH_MOUSE_ID};
+
+/// What the player asks the game to do.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Command {
+    /// A change to the board.
+    Play(Action),
+    /// Where the mouse or finger drawing the chain is, `None` once it lets go.
+    Point(Option<Coordinates>),
+    /// Shows the cursor, and moves it a cell when it was showing already.
+    Cursor(Option<Direction>),
+    Pause,
+    Quit,
+    Scores,
+    /// Flips through the high score tables.
+    Browse(i32),
+    /// Answers the offer to go back to the last game.
+    Resume(bool),
+    /// Moves around the buttons of that offer.
+    Offer(Navigation),
+}
+
+pub struct Input {
+    bindings: Bindings,
+    controllers: Controllers,
+    touch: Touch,
+}
+
+impl Input {
+    pub fn new(bindings: Bindings, controllers: Controllers) -> Self {
+        Input { bindings, controllers, touch: Touch::default() }
+    }
+
+    /// The commands `event` makes for `game`, shown in a window of `size`.
+    pub fn translate(&mut self, event: &Event, game: &Game, size: (u32, u32)) -> Vec<Command> {
+        match *event {
+            Event::Quit { .. } => vec![Command::Quit],
+            Event::KeyDown { keycode: Some(keycode @ Keycode::Y), .. } |
+            Event::KeyDown { keycode: Some(keycode @ Keycode::N), .. } if game.is_offering() => {
+                vec![Command::Resume(keycode == Keycode::Y)]
+            },
+            _ if game.is_offering() => match self.navigate(event, size) {
+                Some(Navigation::Quit) => vec![Command::Quit],
+                Some(navigation) => vec![Command::Offer(navigation)],
+                None => Vec::new(),
+            },
+            Event::KeyDown { keycode: Some(keycode), keymod, repeat, .. } => {
+                let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+
+                match self.bindings.key(keycode, ctrl) {
+                    Some(Control::Select) if repeat => Vec::new(),
+                    Some(control) => press(control, game),
+                    None => Vec::new(),
+                }
+            },
+            Event::KeyUp { keycode: Some(keycode), keymod, .. } => {
+                let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+                release(self.bindings.key(keycode, ctrl))
+            },
+            Event::ControllerDeviceAdded { which, .. } => {
+                self.controllers.add(which);
+                Vec::new()
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(which);
+                Vec::new()
+            },
+            Event::ControllerButtonDown { button, .. } => {
+                self.bindings.button(button).map_or_else(Vec::new, |control| press(control, game))
+            },
+            Event::ControllerButtonUp { button, .. } => release(self.bindings.button(button)),
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                self.controllers.stick(which, axis, value).map_or_else(Vec::new, |direction| press(Control::Move(direction), game))
+            },
+            // Made up by SDL from touches, which come in as fingers too.
+            Event::MouseButtonDown { which: TOUCH_MOUSE_ID, .. } |
+            Event::MouseMotion { which: TOUCH_MOUSE_ID, .. } |
+            Event::MouseButtonUp { which: TOUCH_MOUSE_ID, .. } => Vec::new(),
+            Event::MouseButtonDown { x, y, mouse_btn: MouseButton::Left, .. } => point_down(x, y, game),
+            Event::MouseButtonDown { mouse_btn: MouseButton::Right, .. } => {
+                vec![Command::Play(Action::Cancel), Command::Point(None)]
+            },
+            Event::MouseMotion { x, y, mousestate, .. } if mousestate.is_mouse_button_pressed(MouseButton::Left) => {
+                point_move(x, y, game)
+            },
+            Event::MouseButtonUp { .. } => point_up(),
+            Event::FingerDown { touch_id, finger_id, x, y, .. } if self.touch.down((touch_id, finger_id)) => {
+                let (x, y) = touch::to_window(x, y, size);
+                point_down(x, y, game)
+            },
+            Event::FingerMotion { touch_id, finger_id, x, y, .. } if self.touch.moved((touch_id, finger_id)) => {
+                let (x, y) = touch::to_window(x, y, size);
+                point_move(x, y, game)
+            },
+            Event::FingerUp { touch_id, finger_id, .. } if self.touch.up((touch_id, finger_id)) => point_up(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// How `event` moves around a menu shown in a window of `size`.
+    pub fn navigate(&mut self, event: &Event, size: (u32, u32)) -> Option<Navigation> {
+        match *event {
+            Event::Quit { .. } => Some(Navigation::Quit),
+            Event::KeyDown { keycode: Some(keycode), keymod, .. } => {
+                let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+                self.bindings.key(keycode, ctrl).and_then(navigation)
+            },
+            Event::ControllerDeviceAdded { which, .. } => {
+                self.controllers.add(which);
+                None
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.remove(which);
+                None
+            },
+            Event::ControllerButtonDown { button, .. } => self.bindings.button(button).and_then(navigation),
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                self.controllers.stick(which, axis, value).and_then(|direction| navigation(Control::Move(direction)))
+            },
+            Event::MouseButtonDown { which: TOUCH_MOUSE_ID, .. } |
+            Event::MouseMotion { which: TOUCH_MOUSE_ID, .. } => None,
+            Event::MouseMotion { x, y, .. } => Some(Navigation::Hover(Coordinates { x, y })),
+            Event::MouseButtonDown { x, y, mouse_btn: MouseButton::Left, .. } => Some(Navigation::Click(Coordinates { x, y })),
+            Event::MouseButtonDown { mouse_btn: MouseButton::Right, .. } => Some(Navigation::Back),
+            Event::FingerDown { x, y, .. } => {
+                let (x, y) = touch::to_window(x, y, size);
+                Some(Navigation::Click(Coordinates { x, y }))
+            },
+            // A menu can open while a finger is drawing, which has to be let go of all the same.
+            Event::FingerUp { touch_id, finger_id, .. } => {
+                self.touch.up((touch_id, finger_id));
+                None
+            },
+            _ => None,
+        }
+    }
+}
+
+/// What a key or button bound to `control` does in a menu.
+fn navigation(control: Control) -> Option<Navigation> {
+    match control {
+        Control::Move(Direction::Up) => Some(Navigation::Up),
+        Control::Move(Direction::Down) => Some(Navigation::Down),
+        Control::Select => Some(Navigation::Choose),
+        Control::Retract | Control::Cancel | Control::Pause => Some(Navigation::Back),
+        Control::Quit => Some(Navigation::Quit),
+        _ => None,
+    }
+}
+
+/// A key or button bound to `control` going down.
+fn press(control: Control, game: &Game) -> Vec<Command> {
+    let board = game.session().board();
+    let drawing = !board.chain().is_empty();
+
+    let command = match control {
+        Control::Move(Direction::Left) if game.is_browsing() => Command::Browse(-1),
+        Control::Move(Direction::Right) if game.is_browsing() => Command::Browse(1),
+        Control::Move(direction) => match game.cursor().filter(|_| drawing) {
+            Some(cell) => Command::Play(Action::Extend(cursor::step(cell, direction, board.width(), board.height()))),
+            None => Command::Cursor(Some(direction)),
+        },
+        Control::Select => {
+            let cell = game.cursor().unwrap_or_else(|| cursor::centered(board.width(), board.height()));
+            return vec![Command::Cursor(None), Command::Play(Action::Start(cell))];
+        },
+        Control::Retract if drawing => Command::Play(Action::Retract),
+        Control::Retract | Control::Undo => Command::Play(Action::Undo),
+        Control::Cancel | Control::Pause if drawing => Command::Play(Action::Cancel),
+        Control::Cancel => return Vec::new(),
+        Control::Pause if game.is_browsing() => Command::Scores,
+        Control::Pause => Command::Pause,
+        Control::Quit => Command::Quit,
+        Control::Scores => Command::Scores,
+    };
+
+    vec![command]
+}
+
+/// A key or button going up: only letting go of select does anything, it clears the chain.
+fn release(control: Option<Control>) -> Vec<Command> {
+    match control {
+        Some(Control::Select) => vec![Command::Play(Action::Commit)],
+        _ => Vec::new(),
+    }
+}
+
+fn point_down(x: i32, y: i32, game: &Game) -> Vec<Command> {
+    let point = Command::Point(Some(Coordinates { x, y }));
+
+    match game.cell_at(x, y) {
+        Some(cell) => vec![point, Command::Play(Action::Start(cell))],
+        None => vec![point],
+    }
+}
+
+fn point_move(x: i32, y: i32, game: &Game) -> Vec<Command> {
+    if game.session().board().chain().is_empty() {
+        return Vec::new();
+    }
+
+    let point = Command::Point(Some(Coordinates { x, y }));
+
+    match game.cell_at(x, y) {
+        Some(cell) => vec![point, Command::Play(Action::Extend(cell))],
+        None => vec![point],
+    }
+}
+
+fn point_up() -> Vec<Command> {
+    vec![Command::Play(Action::Commit), Command::Point(None)]
+}
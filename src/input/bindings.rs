@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sdl2::controller::Button;
+use sdl2::keyboard::Keycode;
+use serde::Deserialize;
+
+use cats::scores;
+
+use crate::game::cursor::Direction;
+
+/// Something a key or controller button can be bound to.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Control {
+    Move(Direction),
+    /// Held down to draw a chain from the cursor, let go to clear it.
+    Select,
+    /// Takes the last dot off the chain, or the last move back without one.
+    Retract,
+    Cancel,
+    Undo,
+    /// Drops the chain being drawn, or pauses without one.
+    Pause,
+    Quit,
+    Scores,
+}
+
+/// Names of the controls in the bindings file.
+const CONTROLS: [(Control, &str); 11] = [
+    (Control::Move(Direction::Up), "up"),
+    (Control::Move(Direction::Down), "down"),
+    (Control::Move(Direction::Left), "left"),
+    (Control::Move(Direction::Right), "right"),
+    (Control::Select, "select"),
+    (Control::Retract, "retract"),
+    (Control::Cancel, "cancel"),
+    (Control::Undo, "undo"),
+    (Control::Pause, "pause"),
+    (Control::Quit, "quit"),
+    (Control::Scores, "scores"),
+];
+
+impl std::str::FromStr for Control {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        CONTROLS.iter()
+            .find(|(_, known)| *known == name)
+            .map(|(control, _)| *control)
+            .ok_or_else(|| format!("unknown control `{}`", name))
+    }
+}
+
+/// A key, with or without Ctrl held.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Key {
+    pub keycode: Keycode,
+    pub ctrl: bool,
+}
+
+/// SDL key names, `Ctrl+` in front for Ctrl held: `Space`, `Ctrl+Z`.
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let (ctrl, key) = match name.find('+').filter(|&plus| name[..plus].eq_ignore_ascii_case("ctrl") && plus + 1 < name.len()) {
+            Some(plus) => (true, &name[plus + 1..]),
+            None => (false, name),
+        };
+
+        Keycode::from_name(key)
+            .map(|keycode| Key { keycode, ctrl })
+            .ok_or_else(|| format!("unknown key `{}`", name))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BindingsFile {
+    #[serde(default)]
+    keys: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    buttons: BTreeMap<String, Vec<String>>,
+}
+
+/// Which keys and controller buttons do what. A bindings file only lists the controls it
+/// changes, each with all of its keys or buttons:
+///
+/// ```toml
+/// [keys]
+/// undo = ["Ctrl+Z", "U"]
+/// cancel = ["C"]
+///
+/// [buttons]
+/// undo = ["back"]
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bindings {
+    keys: Vec<(Key, Control)>,
+    buttons: Vec<(Button, Control)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let key = |keycode| Key { keycode, ctrl: false };
+
+        Bindings {
+            keys: vec![
+                (key(Keycode::Up), Control::Move(Direction::Up)),
+                (key(Keycode::W), Control::Move(Direction::Up)),
+                (key(Keycode::Down), Control::Move(Direction::Down)),
+                (key(Keycode::S), Control::Move(Direction::Down)),
+                (key(Keycode::Left), Control::Move(Direction::Left)),
+                (key(Keycode::A), Control::Move(Direction::Left)),
+                (key(Keycode::Right), Control::Move(Direction::Right)),
+                (key(Keycode::D), Control::Move(Direction::Right)),
+                (key(Keycode::Space), Control::Select),
+                (key(Keycode::Return), Control::Select),
+                (key(Keycode::Backspace), Control::Retract),
+                (Key { keycode: Keycode::Z, ctrl: true }, Control::Undo),
+                (key(Keycode::P), Control::Pause),
+                (key(Keycode::Escape), Control::Pause),
+                (Key { keycode: Keycode::Q, ctrl: true }, Control::Quit),
+                (key(Keycode::H), Control::Scores),
+            ],
+            buttons: vec![
+                (Button::DPadUp, Control::Move(Direction::Up)),
+                (Button::DPadDown, Control::Move(Direction::Down)),
+                (Button::DPadLeft, Control::Move(Direction::Left)),
+                (Button::DPadRight, Control::Move(Direction::Right)),
+                (Button::A, Control::Select),
+                (Button::B, Control::Retract),
+                (Button::X, Control::Cancel),
+                (Button::Back, Control::Undo),
+                (Button::Start, Control::Pause),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    /// The bindings at `path`, the defaults when there is no file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(source) => Bindings::parse(&source),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Bindings::default()),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    /// The defaults with the controls of a bindings file replaced. A key or button the file
+    /// binds loses whatever it did before.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let file: BindingsFile = toml::from_str(source).map_err(|error| error.to_string())?;
+        let mut bindings = Bindings::default();
+
+        for (name, keys) in &file.keys {
+            let control: Control = name.parse()?;
+            let keys = keys.iter().map(|key| key.parse()).collect::<Result<Vec<Key>, _>>()?;
+
+            bindings.keys.retain(|(key, bound)| *bound != control && !keys.contains(key));
+            bindings.keys.extend(keys.into_iter().map(|key| (key, control)));
+        }
+
+        for (name, buttons) in &file.buttons {
+            let control: Control = name.parse()?;
+            let buttons = buttons.iter()
+                .map(|button| Button::from_string(button).ok_or_else(|| format!("unknown button `{}`", button)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            bindings.buttons.retain(|(button, bound)| *bound != control && !buttons.contains(button));
+            bindings.buttons.extend(buttons.into_iter().map(|button| (button, control)));
+        }
+
+        Ok(bindings)
+    }
+
+    /// What `keycode` does, Ctrl held or not. A key bound with Ctrl only works with it,
+    /// and the other way round.
+    pub fn key(&self, keycode: Keycode, ctrl: bool) -> Option<Control> {
+        self.keys.iter().find(|(key, _)| *key == Key { keycode, ctrl }).map(|&(_, control)| control)
+    }
+
+    pub fn button(&self, button: Button) -> Option<Control> {
+        self.buttons.iter().find(|(bound, _)| *bound == button).map(|&(_, control)| control)
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    scores::data_dir().map(|dir| dir.join("keys.toml"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!("Space".parse(), Ok(Key { keycode: Keycode::Space, ctrl: false }));
+        assert_eq!("ctrl+Z".parse(), Ok(Key { keycode: Keycode::Z, ctrl: true }));
+        assert_eq!("+".parse(), Ok(Key { keycode: Keycode::Plus, ctrl: false }));
+        assert!("Ctrl+".parse::<Key>().is_err());
+        assert!("Nope".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn files_replace_the_controls_they_list() {
+        let bindings = Bindings::parse("[keys]\nundo = [\"U\"]\ncancel = [\"C\", \"Ctrl+X\"]\n[buttons]\nselect = [\"x\"]\n").unwrap();
+
+        assert_eq!(bindings.key(Keycode::U, false), Some(Control::Undo));
+        assert_eq!(bindings.key(Keycode::Z, true), None);
+        assert_eq!(bindings.key(Keycode::X, true), Some(Control::Cancel));
+        assert_eq!(bindings.key(Keycode::X, false), None);
+        assert_eq!(bindings.key(Keycode::Space, false), Some(Control::Select));
+        assert_eq!(bindings.button(Button::X), Some(Control::Select));
+        assert_eq!(bindings.key(Keycode::Escape, false), Some(Control::Pause));
+        assert_eq!(bindings.button(Button::A), None);
+
+        assert_eq!(Bindings::parse("").unwrap(), Bindings::default());
+        assert!(Bindings::parse("[keys]\njump = [\"J\"]\n").is_err());
+        assert!(Bindings::parse("[buttons]\nundo = [\"z\"]\n").is_err());
+        assert!(Bindings::parse("undo = [\"U\"]\n").is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use sdl2::GameControllerSubsystem;
+use sdl2::controller::{Axis, GameController};
+
+use crate::game::cursor::Direction;
+
+/// How far a stick has to be pushed to move the cursor, out of `i16::MAX`.
+const STICK_THRESHOLD: i16 = 16_000;
+
+/// Where a left stick is pushed. It moves the cursor a cell each time it is pushed out of the middle.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct Stick {
+    x: i16,
+    y: i16,
+    pointing: Option<Direction>,
+}
+
+impl Stick {
+    /// Takes the new position of an axis, returns the direction the stick was just pushed in.
+    pub fn moved(&mut self, axis: Axis, value: i16) -> Option<Direction> {
+        match axis {
+            Axis::LeftX => self.x = value,
+            Axis::LeftY => self.y = value,
+            _ => return None,
+        }
+
+        let (x, y) = (i32::from(self.x), i32::from(self.y));
+        let pointing = if x.abs().max(y.abs()) < i32::from(STICK_THRESHOLD) {
+            None
+        } else if x.abs() > y.abs() {
+            Some(if x < 0 { Direction::Left } else { Direction::Right })
+        } else {
+            Some(if y < 0 { Direction::Up } else { Direction::Down })
+        };
+
+        let pushed = pointing.filter(|_| pointing != self.pointing);
+        self.pointing = pointing;
+
+        pushed
+    }
+}
+
+/// The plugged in game controllers, by joystick id.
+pub struct Controllers {
+    subsystem: GameControllerSubsystem,
+    open: HashMap<i32, (GameController, Stick)>,
+}
+
+impl Controllers {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        Controllers { subsystem, open: HashMap::new() }
+    }
+
+    /// Opens a controller that was plugged in, or was already at start.
+    pub fn add(&mut self, index: u32) {
+        match self.subsystem.open(index) {
+            Ok(controller) => {
+                self.open.insert(controller.instance_id(), (controller, Stick::default()));
+            },
+            Err(error) => eprintln!("could not open game controller {}: {}", index, error),
+        }
+    }
+
+    pub fn remove(&mut self, id: i32) {
+        self.open.remove(&id);
+    }
+
+    /// The direction the stick of controller `id` was just pushed in, if any.
+    pub fn stick(&mut self, id: i32, axis: Axis, value: i16) -> Option<Direction> {
+        self.open.get_mut(&id).and_then(|(_, stick)| stick.moved(axis, value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sticks_move_once_per_push() {
+        let mut stick = Stick::default();
+
+        assert_eq!(stick.moved(Axis::LeftX, 8_000), None);
+        assert_eq!(stick.moved(Axis::LeftX, 30_000), Some(Direction::Right));
+        assert_eq!(stick.moved(Axis::LeftX, 32_000), None);
+        assert_eq!(stick.moved(Axis::LeftY, -32_767), Some(Direction::Up));
+        assert_eq!(stick.moved(Axis::RightX, -32_767), None);
+        assert_eq!(stick.moved(Axis::LeftY, 0), Some(Direction::Right));
+        assert_eq!(stick.moved(Axis::LeftX, 0), None);
+        assert_eq!(stick.moved(Axis::LeftX, 20_000), Some(Direction::Right));
+    }
+}
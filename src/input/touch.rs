@@ -0,0 +1,68 @@
+/// The mouse id of the mouse events SDL makes up from touches, which are handled as touches instead.
+pub const TOUCH_MOUSE_ID: u32 = u32::MAX;
+
+/// A finger on a touch device.
+pub type Finger = (i64, i64);
+
+/// Follows the finger drawing a chain; others touching the screen meanwhile are ignored.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct Touch {
+    drawing: Option<Finger>,
+}
+
+impl Touch {
+    /// Whether `finger` starts drawing, when no other one is.
+    pub fn down(&mut self, finger: Finger) -> bool {
+        if self.drawing.is_some() {
+            return false;
+        }
+
+        self.drawing = Some(finger);
+        true
+    }
+
+    /// Whether `finger` is the one drawing.
+    pub fn moved(&self, finger: Finger) -> bool {
+        self.drawing == Some(finger)
+    }
+
+    /// Whether the finger drawing was lifted.
+    pub fn up(&mut self, finger: Finger) -> bool {
+        let drawing = self.moved(finger);
+        if drawing {
+            self.drawing = None;
+        }
+
+        drawing
+    }
+}
+
+/// Window coordinates of a touch, given as fractions of the window's `size`.
+pub fn to_window(x: f32, y: f32, size: (u32, u32)) -> (i32, i32) {
+    ((x * size.0 as f32).round() as i32, (y * size.1 as f32).round() as i32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_the_first_finger_draws() {
+        let mut touch = Touch::default();
+
+        assert!(touch.down((1, 10)));
+        assert!(!touch.down((1, 11)));
+        assert!(touch.moved((1, 10)));
+        assert!(!touch.moved((1, 11)));
+        assert!(!touch.moved((2, 10)));
+        assert!(!touch.up((1, 11)));
+        assert!(touch.up((1, 10)));
+        assert!(touch.down((1, 11)));
+    }
+
+    #[test]
+    fn touches_scale_to_the_window() {
+        assert_eq!(to_window(0.0, 1.0, (400, 600)), (0, 600));
+        assert_eq!(to_window(0.5, 0.25, (401, 600)), (201, 150));
+    }
+}
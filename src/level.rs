@@ -0,0 +1,502 @@
+//! Levels are TOML files under `assets/levels/`:
+//!
+//! ```toml
+//! name = "Warm up"
+//! colors = ["red", "blue", "green", "purple"]   # optional, the five default colors otherwise
+//! moves = 20                                     # or `time = 60` in seconds, endless without either
+//! goals = { red = 20, blue = 15 }                # optional
+//! loop = "bombs"                                 # what loops do to enclosed dots: none, clear or bombs
+//! undo = 3                                       # turns that can be taken back, see `Mode::undo_limit`
+//! layout = '''
+//! R R . . B B
+//! R . . . . B
+//! . . # # . .
+//! . . # # . .
+//! G . . . . P
+//! G G . . P P
+//! '''
+//! ```
+//!
+//! The layout gives one row of the board per line: a color letter (see `Color::symbol`) places
+//! that dot, `.` a random dot and `#` a wall. Without a layout the board is `width` by `height`
+//! random dots.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::engine::board::{MIN_SIZE, MAX_SIZE};
+use crate::engine::{Board, Color, Config, Goal, Layout, LoopEffect, Mode, Session, Tile};
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Level {
+    pub name: String,
+    pub board: Config,
+    pub mode: Mode,
+    pub goals: Vec<Goal>,
+    pub layout: Layout,
+    /// Undos allowed instead of the mode's.
+    pub undo: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    /// Problem in the level file, `line` and `column` start at 1.
+    Parse { line: usize, column: usize, message: String },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "{}", error),
+            LevelError::Parse { line, column, message } => write!(f, "{}:{}: {}", line, column, message),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+impl From<io::Error> for LevelError {
+    fn from(error: io::Error) -> Self {
+        LevelError::Io(error)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelFile {
+    name: Option<String>,
+    width: Option<Spanned<usize>>,
+    height: Option<Spanned<usize>>,
+    colors: Option<Vec<Spanned<String>>>,
+    moves: Option<Spanned<u32>>,
+    time: Option<Spanned<u64>>,
+    #[serde(default)]
+    goals: BTreeMap<String, Spanned<u32>>,
+    layout: Option<Spanned<String>>,
+    #[serde(rename = "loop")]
+    loop_effect: Option<Spanned<String>>,
+    undo: Option<u32>,
+}
+
+impl Level {
+    /// A level of random dots without a name, the undo limit coming from `mode`.
+    pub fn random(board: Config, mode: Mode, goals: Vec<Goal>) -> Level {
+        let layout = vec![vec![Tile::Random; board.height]; board.width];
+
+        Level { name: String::new(), board, mode, goals, layout, undo: None }
+    }
+
+    pub fn load(path: &Path) -> Result<Level, LevelError> {
+        Level::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(source: &str) -> Result<Level, LevelError> {
+        let parser = Parser { source };
+        let file: LevelFile = toml::from_str(source).map_err(|error| parser.toml_error(&error))?;
+
+        parser.level(file)
+    }
+
+    /// The level in the file format `parse` reads.
+    pub fn to_toml(&self) -> String {
+        let mut source = String::new();
+        let quoted = |text: &str| toml::Value::String(text.to_string()).to_string();
+
+        if !self.name.is_empty() {
+            source += &format!("name = {}\n", quoted(&self.name));
+        }
+
+        let colors: Vec<String> = self.board.colors.iter().map(|color| quoted(color.name())).collect();
+        source += &format!("colors = [{}]\n", colors.join(", "));
+
+        match self.mode {
+            Mode::Endless => (),
+            Mode::Moves(moves) => source += &format!("moves = {}\n", moves),
+            Mode::Timed(time) => source += &format!("time = {}\n", time.as_secs()),
+        }
+
+        if !self.goals.is_empty() {
+            let goals: Vec<String> = self.goals.iter().map(|goal| format!("{} = {}", goal.color, goal.target)).collect();
+            source += &format!("goals = {{ {} }}\n", goals.join(", "));
+        }
+
+        if self.board.loop_effect != LoopEffect::None {
+            source += &format!("loop = {}\n", quoted(self.board.loop_effect.name()));
+        }
+
+        if let Some(undo) = self.undo {
+            source += &format!("undo = {}\n", undo);
+        }
+
+        source += "layout = '''\n";
+        for y in 0..self.board.height {
+            let row: Vec<String> = self.layout.iter()
+                .map(|column| match column[y] {
+                    Tile::Random => '.',
+                    Tile::Wall => '#',
+                    Tile::Dot(color) => color.symbol(),
+                }.to_string())
+                .collect();
+            source += &row.join(" ");
+            source += "\n";
+        }
+        source += "'''\n";
+
+        source
+    }
+
+    pub fn board(&self, seed: u64) -> Board {
+        Board::with_layout(&self.board, &self.layout, seed)
+    }
+
+    pub fn session(&self, seed: u64) -> Session {
+        Session::with_board(self.board(seed), self.mode)
+            .with_goals(self.goals.clone())
+            .with_undo_limit(self.undo.or_else(|| self.mode.undo_limit()))
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn level(&self, file: LevelFile) -> Result<Level, LevelError> {
+        let colors = match file.colors {
+            Some(colors) => self.colors(colors)?,
+            None => Color::DEFAULT.to_vec(),
+        };
+
+        let mode = match (file.moves, file.time) {
+            (Some(_), Some(time)) => return Err(self.error(time.start(), "only one of `moves` and `time` can be given")),
+            (Some(moves), None) if *moves.get_ref() == 0 => return Err(self.error(moves.start(), "a level needs at least one move")),
+            (Some(moves), None) => Mode::Moves(moves.into_inner()),
+            (None, Some(time)) if *time.get_ref() == 0 => return Err(self.error(time.start(), "a level needs at least one second")),
+            (None, Some(time)) => Mode::Timed(Duration::from_secs(time.into_inner())),
+            (None, None) => Mode::Endless,
+        };
+
+        let mut goals = Vec::new();
+        for (name, target) in file.goals {
+            let color: Color = name.parse().map_err(|message| self.error(target.start(), message))?;
+            if !colors.contains(&color) {
+                return Err(self.error(target.start(), format!("goal color {} is not one of the level colors", color)));
+            }
+            if *target.get_ref() == 0 {
+                return Err(self.error(target.start(), "goal count must be at least 1"));
+            }
+            goals.push(Goal::new(color, target.into_inner()));
+        }
+        goals.sort_by_key(|goal| colors.iter().position(|color| *color == goal.color));
+
+        let loop_effect = match file.loop_effect {
+            Some(effect) => effect.get_ref().parse().map_err(|message| self.error(effect.start(), message))?,
+            None => LoopEffect::None,
+        };
+
+        let width = file.width.map(|width| self.size(width)).transpose()?;
+        let height = file.height.map(|height| self.size(height)).transpose()?;
+
+        let layout = match file.layout {
+            Some(layout) => {
+                let start = layout.start();
+                let layout = self.layout(layout, &colors)?;
+
+                if width.is_some_and(|width| width != layout.len()) || height.is_some_and(|height| height != layout[0].len()) {
+                    return Err(self.error(start, "layout does not match `width` and `height`"));
+                }
+
+                layout
+            },
+            None => {
+                let width = width.ok_or_else(|| self.error(0, "a level needs either a `layout` or a `width`"))?;
+                let height = height.ok_or_else(|| self.error(0, "a level needs either a `layout` or a `height`"))?;
+
+                vec![vec![Tile::Random; height]; width]
+            },
+        };
+
+        Ok(Level {
+            name: file.name.unwrap_or_default(),
+            board: Config {
+                width: layout.len(),
+                height: layout[0].len(),
+                colors,
+                loop_effect,
+            },
+            mode,
+            goals,
+            layout,
+            undo: file.undo,
+        })
+    }
+
+    fn colors(&self, names: Vec<Spanned<String>>) -> Result<Vec<Color>, LevelError> {
+        let mut colors: Vec<Color> = Vec::new();
+
+        for name in &names {
+            let color: Color = name.get_ref().parse().map_err(|message| self.error(name.start(), message))?;
+            if colors.contains(&color) {
+                return Err(self.error(name.start(), format!("color {} is listed twice", color)));
+            }
+            colors.push(color);
+        }
+
+        if colors.len() < 2 {
+            return Err(self.error(names.first().map_or(0, Spanned::start), "a level needs at least 2 colors"));
+        }
+
+        Ok(colors)
+    }
+
+    fn size(&self, size: Spanned<usize>) -> Result<usize, LevelError> {
+        if !(MIN_SIZE..=MAX_SIZE).contains(size.get_ref()) {
+            return Err(self.error(size.start(), format!("board sides must be between {} and {}", MIN_SIZE, MAX_SIZE)));
+        }
+
+        Ok(size.into_inner())
+    }
+
+    /// Turns the rows of the layout into columns of tiles.
+    fn layout(&self, layout: Spanned<String>, colors: &[Color]) -> Result<Layout, LevelError> {
+        let content = self.content(layout.start()..layout.end(), layout.get_ref());
+        let mut rows: Vec<Vec<Tile>> = Vec::new();
+
+        for (offset, line) in lines(layout.get_ref()) {
+            let mut row = Vec::new();
+
+            for (index, symbol) in line.char_indices().filter(|(_, symbol)| !symbol.is_whitespace()) {
+                let position = content.clone().map_or(layout.start(), |content| content.start + offset + index);
+                let tile = match symbol {
+                    '.' => Tile::Random,
+                    '#' => Tile::Wall,
+                    symbol => match Color::from_symbol(symbol) {
+                        Some(color) if colors.contains(&color) => Tile::Dot(color),
+                        Some(color) => return Err(self.error(position, format!("color {} is not one of the level colors", color))),
+                        None => return Err(self.error(position, format!("unknown layout symbol `{}`", symbol))),
+                    },
+                };
+                row.push(tile);
+            }
+
+            if row.is_empty() {
+                continue;
+            }
+
+            if let Some(first) = rows.first() {
+                if first.len() != row.len() {
+                    let position = content.clone().map_or(layout.start(), |content| content.start + offset);
+                    return Err(self.error(position, format!("row has {} cells, expected {}", row.len(), first.len())));
+                }
+            }
+
+            rows.push(row);
+        }
+
+        let (width, height) = (rows.first().map_or(0, Vec::len), rows.len());
+        if !(MIN_SIZE..=MAX_SIZE).contains(&width) || !(MIN_SIZE..=MAX_SIZE).contains(&height) {
+            return Err(self.error(layout.start(), format!("layout sides must be between {} and {}", MIN_SIZE, MAX_SIZE)));
+        }
+
+        Ok((0..width).map(|x| rows.iter().map(|row| row[x]).collect()).collect())
+    }
+
+    /// Where the text of a string value starts in the source, if it is written out verbatim
+    /// (no escapes), so positions inside the value can be reported.
+    fn content(&self, span: Range<usize>, value: &str) -> Option<Range<usize>> {
+        let raw = self.source.get(span.clone())?;
+        let quote = ["'''", "\"\"\"", "'", "\""].iter().find(|quote| raw.starts_with(*quote))?;
+        let mut start = span.start + quote.len();
+
+        if quote.len() == 3 && self.source[start..].starts_with('\n') {
+            start += 1;
+        } else if quote.len() == 3 && self.source[start..].starts_with("\r\n") {
+            start += 2;
+        }
+
+        let content = start..start + value.len();
+
+        if self.source.get(content.clone()) == Some(value) {
+            Some(content)
+        } else {
+            None
+        }
+    }
+
+    fn toml_error(&self, error: &toml::de::Error) -> LevelError {
+        let message = error.to_string();
+        let message = match message.find(" at line ") {
+            Some(end) => message[..end].to_string(),
+            None => message,
+        };
+
+        // Unknown keys are reported at the start of their table, point at the key itself.
+        if message.starts_with("unknown field") {
+            let key = message.split('`').nth(1).unwrap_or_default();
+            let found = lines(self.source).find(|(_, line)| line.trim_start().starts_with(key));
+
+            if let Some((offset, line)) = found {
+                return self.error(offset + line.len() - line.trim_start().len(), message);
+            }
+        }
+
+        let (line, column) = error.line_col().unwrap_or((0, 0));
+
+        LevelError::Parse { line: line + 1, column: column + 1, message }
+    }
+
+    fn error<M: Into<String>>(&self, offset: usize, message: M) -> LevelError {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+
+        LevelError::Parse { line, column, message: message.into() }
+    }
+}
+
+/// Lines of `text` with their byte offsets.
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+
+    text.split('\n').map(move |line| {
+        let start = offset;
+        offset += line.len() + 1;
+        (start, line.trim_end_matches('\r'))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_error(source: &str) -> (usize, usize, String) {
+        match Level::parse(source) {
+            Err(LevelError::Parse { line, column, message }) => (line, column, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_level() {
+        let level = Level::parse(r#"
+name = "Warm up"
+colors = ["red", "blue", "green"]
+moves = 25
+goals = { red = 20, blue = 15 }
+loop = "clear"
+layout = '''
+R . B
+# . G
+'''
+"#).unwrap();
+
+        assert_eq!(level.name, "Warm up");
+        assert_eq!(level.board, Config {
+            width: 3,
+            height: 2,
+            colors: vec![Color::Red, Color::Blue, Color::Green],
+            loop_effect: LoopEffect::Clear,
+        });
+        assert_eq!(level.mode, Mode::Moves(25));
+        assert_eq!(level.goals, vec![Goal::new(Color::Red, 20), Goal::new(Color::Blue, 15)]);
+        assert_eq!(level.layout, vec![
+            vec![Tile::Dot(Color::Red), Tile::Wall],
+            vec![Tile::Random, Tile::Random],
+            vec![Tile::Dot(Color::Blue), Tile::Dot(Color::Green)],
+        ]);
+
+        let session = level.session(1);
+        assert_eq!(session.board().color(engine_cell(0, 0)), Some(Color::Red));
+        assert!(session.board().is_wall(engine_cell(0, 1)));
+        assert_eq!(session.goals().len(), 2);
+    }
+
+    fn engine_cell(x: usize, y: usize) -> crate::engine::Cell {
+        crate::engine::Cell::new(x, y)
+    }
+
+    #[test]
+    fn writes_levels_it_can_read() {
+        let level = Level::parse("name = \"It's \\\"quoted\\\"\"\ntime = 30\ngoals = { blue = 3, red = 2 }\nloop = \"bombs\"\nundo = 2\nlayout = '''\nR . B\n# . G\n'''\n").unwrap();
+        assert_eq!(level.undo, Some(2));
+        assert_eq!(level.session(1).undos_left(), Some(2));
+        assert_eq!(Level::parse(&level.to_toml()).unwrap(), level);
+
+        let level = Level::parse("width = 4\nheight = 3\n").unwrap();
+        assert_eq!(Level::parse(&level.to_toml()).unwrap(), level);
+    }
+
+    #[test]
+    fn parses_level_without_layout() {
+        let level = Level::parse("width = 7\nheight = 9\ntime = 60\n").unwrap();
+
+        assert_eq!((level.board.width, level.board.height), (7, 9));
+        assert_eq!(level.board.colors, Color::DEFAULT.to_vec());
+        assert_eq!(level.mode, Mode::Timed(Duration::from_secs(60)));
+        assert!(level.goals.is_empty());
+        assert!(level.layout.iter().flatten().all(|tile| *tile == Tile::Random));
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_position() {
+        assert_eq!(parse_error("width = 5\nheight = = 5\n").0, 2);
+        assert_eq!(parse_error("width = 5\nheight = 5\n  speed = 3\n").0, 3);
+        assert_eq!(parse_error("width = 5\nheight = 5\n  speed = 3\n").1, 3);
+
+        let (line, column, message) = parse_error("width = \"five\"\nheight = 5\n");
+        assert_eq!((line, column), (1, 9));
+        assert!(message.contains("invalid type"), "{}", message);
+    }
+
+    #[test]
+    fn reports_semantic_errors_with_position() {
+        assert_eq!(parse_error("width = 5\nheight = 40\n"), (2, 10, "board sides must be between 2 and 16".to_string()));
+        assert_eq!(parse_error("width = 5\nheight = 5\ncolors = [\"red\", \"pink\"]\n").0, 3);
+        assert_eq!(parse_error("width = 5\nheight = 5\ncolors = [\"red\", \"pink\"]\n").1, 18);
+        assert_eq!(parse_error("width = 5\nheight = 5\nmoves = 3\ntime = 4\n").0, 4);
+        assert_eq!(parse_error("width = 5\nheight = 5\ngoals = { gray = 3 }\n").0, 3);
+        assert_eq!(parse_error("width = 5\n").2, "a level needs either a `layout` or a `height`");
+        assert_eq!(parse_error("width = 5\nheight = 5\nloop = \"boom\"\n").0, 3);
+    }
+
+    #[test]
+    fn reports_layout_errors_at_the_symbol() {
+        let source = "colors = [\"red\", \"blue\"]\nlayout = '''\nR B\nB x\n'''\n";
+        assert_eq!(parse_error(source), (4, 3, "unknown layout symbol `x`".to_string()));
+
+        let source = "colors = [\"red\", \"blue\"]\nlayout = '''\nR B\nB G\n'''\n";
+        assert_eq!(parse_error(source), (4, 3, "color green is not one of the level colors".to_string()));
+
+        let source = "layout = '''\nR B\nB B B\n'''\n";
+        assert_eq!(parse_error(source), (3, 1, "row has 3 cells, expected 2".to_string()));
+
+        let source = "width = 3\nlayout = '''\nR B\nB B\n'''\n";
+        assert_eq!(parse_error(source), (2, 10, "layout does not match `width` and `height`".to_string()));
+    }
+
+    #[test]
+    fn bundled_levels_load() {
+        let mut paths: Vec<_> = fs::read_dir("assets/levels").unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .collect();
+        paths.sort();
+
+        assert!(!paths.is_empty());
+        paths.iter().for_each(|path| {
+            if let Err(error) = Level::load(path) {
+                panic!("{}: {}", path.display(), error);
+            }
+        });
+    }
+}
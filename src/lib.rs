@@ -0,0 +1,14 @@
+extern crate rand;
+extern crate rand_pcg;
+extern crate serde;
+extern crate toml;
+
+pub mod ai;
+pub mod args;
+pub mod engine;
+pub mod level;
+pub mod replay;
+pub mod save;
+pub mod scores;
+
+mod serde_u64;
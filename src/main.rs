@@ -1,30 +1,110 @@
 extern crate sdl2;
+extern crate cats;
 extern crate rand;
+extern crate serde;
+extern crate toml;
 
 pub(crate) mod game;
+pub(crate) mod input;
+pub(crate) mod options;
+pub(crate) mod screens;
 
-use std::time::Duration;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::mouse::MouseButton;
 use sdl2::render::BlendMode;
 use sdl2::image::InitFlag;
 
+use cats::engine::{Config, Tile};
+use cats::level::Level;
+use cats::replay::Replay;
+use cats::save::{self, SavedGame};
+use cats::scores::{self, HighScores, Key};
+
 use game::Game;
+use game::board::Board;
 use game::color::BACKGROUND;
+use input::{Command, Input};
+use input::bindings::{self, Bindings};
+use input::controllers::Controllers;
+use options::Options;
+use screens::{Choice, Screens};
 
 pub const FIELD_OFFSET_LEFT: i32 = 50;
 pub const FIELD_OFFSET_TOP: i32 = 100;
 pub const TILE_SIZE: i32 = 60;
 
 fn main() {
+    let options = Options::from_env().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    });
+    let replay = options.replay.as_ref().map(|path| Replay::load(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path.display(), error);
+        std::process::exit(2);
+    }));
+    let seed = match &replay {
+        Some(replay) => replay.seed,
+        None => options.seed.unwrap_or_else(rand::random),
+    };
+    let level = match (&replay, &options.level) {
+        (Some(replay), _) => replay.level.clone(),
+        (None, Some(path)) => Level::load(path).unwrap_or_else(|error| {
+            eprintln!("{}:{}", path.display(), error);
+            std::process::exit(2);
+        }),
+        (None, None) => options.custom_level(),
+    };
+
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
+    let bindings = match bindings::default_path() {
+        Some(path) => Bindings::load(&path).unwrap_or_else(|error| {
+            eprintln!("{}: {}, using the default controls", path.display(), error);
+            Bindings::default()
+        }),
+        None => Bindings::default(),
+    };
+    let mut input = Input::new(bindings, Controllers::new(sdl_context.game_controller().unwrap()));
 
     let _image_context = sdl2::image::init(InitFlag::PNG | InitFlag::JPG);
 
-    let window = video_subsystem.window("cats", 400, 420)
+    // Replays don't go into the high scores.
+    let high_scores = match scores::default_path().filter(|_| replay.is_none()) {
+        Some(path) => {
+            let (high_scores, error) = HighScores::open(&path);
+            if let Some(error) = error {
+                eprintln!("{}: {}, starting with empty high scores", path.display(), error);
+            }
+            high_scores
+        },
+        None => HighScores::in_memory(),
+    };
+    let key = Key::for_level(&level, options.seed);
+    let mut game = Game::new(level.name.clone(), level.session(seed), key, high_scores);
+
+    if let Some(delay) = options.hint {
+        game.show_hints_after(Some(delay).filter(|delay| delay.as_secs() > 0));
+    }
+
+    match replay {
+        Some(replay) => game.play_back(replay),
+        None if options.record.is_some() => game.record(Replay::new(level.clone(), seed)),
+        None => (),
+    }
+
+    // A recorded or replayed game has to start from the level, so it is neither resumed nor saved.
+    let save_path = save::default_path().filter(|_| options.replay.is_none() && options.record.is_none());
+    if let Some(path) = &save_path {
+        match SavedGame::load(path) {
+            Ok(Some(saved)) => game.offer_resume(saved),
+            Ok(None) => (),
+            Err(error) => eprintln!("{}: {}, starting a new game", path.display(), error),
+        }
+    }
+
+    let (width, height) = Board::window_size(&level.board);
+    let window = video_subsystem.window(&game.title(), width, height)
         .position_centered()
         .opengl()
         .build()
@@ -40,41 +120,131 @@ fn main() {
     canvas.clear();
     canvas.present();
 
-    let mut game = Game::new();
-
     let mut event_pump = sdl_context.event_pump().unwrap();
+    let mut last_frame = Instant::now();
+    let mut screens = Screens::default();
+    // The level and seed to start over from.
+    let mut current = Some((level.clone(), seed));
 
     'running: loop {
         for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} |
-                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                Event::MouseButtonDown { x, y, mouse_btn, .. } => {
-                    if mouse_btn == MouseButton::Left {
-                        game.handle_click(x, y);
-                    }
-                },
-                Event::MouseMotion {x, y, mousestate, ..} => {
-                    if mousestate.is_mouse_button_pressed(MouseButton::Left) {
-                        game.handle_move(x, y);
-                    }
-                },
-                Event::MouseButtonUp { .. } => {
-                    game.handle_raise();
-                },
-                _ => ()
+            let size = canvas.window().size();
+
+            if screens.is_open() {
+                match input.navigate(&event, size).and_then(|navigation| screens.navigate(navigation, size)) {
+                    Some(Choice::Quit) => {
+                        quit(&mut game, save_path.as_deref(), options.record.as_deref());
+                        break 'running
+                    },
+                    Some(Choice::Restart) => {
+                        if let Some((level, seed)) = &current {
+                            save_recording(&mut game, options.record.as_deref());
+                            game.start(level.name.clone(), level.session(*seed), Key::for_level(level, options.seed));
+                        }
+                    },
+                    Some(Choice::NewGame(mode)) => {
+                        let board = game.session().board();
+                        let config = Config {
+                            width: board.width(),
+                            height: board.height(),
+                            colors: board.colors().to_vec(),
+                            loop_effect: board.loop_effect(),
+                        };
+                        let level = Level {
+                            name: String::new(),
+                            layout: vec![vec![Tile::Random; config.height]; config.width],
+                            board: config,
+                            mode,
+                            goals: Vec::new(),
+                            undo: None,
+                        };
+                        let seed = rand::random();
+
+                        save_recording(&mut game, options.record.as_deref());
+                        game.start(String::new(), level.session(seed), Key::for_level(&level, None));
+                        canvas.window_mut().set_title(&game.title()).unwrap();
+                        current = Some((level, seed));
+                    },
+                    Some(Choice::Hints(on)) => {
+                        let delay = options.hint.filter(|delay| delay.as_secs() > 0).unwrap_or(game::HINT_DELAY);
+                        game.show_hints_after(Some(delay).filter(|_| on));
+                    },
+                    None => (),
+                }
+                continue;
+            }
+
+            for command in input.translate(&event, &game, size) {
+                let command = match command {
+                    Command::Offer(navigation) => match game.navigate_offer(navigation, size) {
+                        Some(resume) => Command::Resume(resume),
+                        None => continue,
+                    },
+                    command => command,
+                };
+
+                match command {
+                    Command::Quit => {
+                        quit(&mut game, save_path.as_deref(), options.record.as_deref());
+                        break 'running
+                    },
+                    Command::Pause if !game.is_offering() => screens.pause(current.is_some(), game.shows_hints()),
+                    Command::Resume(resume) => {
+                        game.answer_resume(resume);
+
+                        // The level the game was started from is not saved with it.
+                        if resume {
+                            current = None;
+                        }
+
+                        if let Some(path) = &save_path {
+                            if let Err(error) = SavedGame::remove(path) {
+                                eprintln!("{}: {}", path.display(), error);
+                            }
+                        }
+
+                        let board = game.session().board();
+                        let (width, height) = Board::window_size(&Config { width: board.width(), height: board.height(), ..Config::default() });
+                        let window = canvas.window_mut();
+                        window.set_size(width, height).unwrap();
+                        window.set_title(&game.title()).unwrap();
+                    },
+                    command => game.handle(command),
+                }
             }
         }
 
         canvas.set_draw_color(BACKGROUND);
         canvas.clear();
 
-        game.update();
+        // Nothing moves while a menu is open.
+        let now = Instant::now();
+        if !screens.is_open() {
+            game.update(now - last_frame);
+        }
+        last_frame = now;
         game.render(&mut canvas);
+        screens.render(&mut canvas);
 
         canvas.present();
         std::thread::sleep(Duration::from_millis(16));
     }
 }
+
+/// Leaves the game: saves it to resume, or puts it into the high scores, and writes the recording.
+fn quit(game: &mut Game, save_path: Option<&Path>, record: Option<&Path>) {
+    if let Err(error) = game.quit(save_path) {
+        eprintln!("could not save the game: {}", error);
+        game.finish();
+    }
+
+    save_recording(game, record);
+}
+
+fn save_recording(game: &mut Game, path: Option<&Path>) {
+    if let (Some(path), Some(replay)) = (path, game.take_recording()) {
+        if let Err(error) = replay.save(path) {
+            eprintln!("{}: {}", path.display(), error);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cats::args::{parse_colors, parse_mode, parse_size};
+use cats::engine::{Config, Goal, Mode};
+use cats::level::Level;
+
+const USAGE: &str = "usage: cats [--replay <file>] [--record <file>] [--hint <seconds>] [--seed <number>] [--level <file> | [--size <width>x<height>] [--colors <count>|<color,...>] [--moves <count> | --time <seconds>] [--goal <color>:<count>]... [--loop none|clear|bombs]]";
+
+/// Command line settings for a game.
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub seed: Option<u64>,
+    pub board: Config,
+    pub mode: Mode,
+    pub goals: Vec<Goal>,
+    pub level: Option<PathBuf>,
+    /// Where to write a replay of the game.
+    pub record: Option<PathBuf>,
+    /// A replay to watch instead of playing.
+    pub replay: Option<PathBuf>,
+    /// Idle time before a chain is suggested, zero for no hints.
+    pub hint: Option<Duration>,
+}
+
+impl Options {
+    pub fn from_env() -> Result<Self, String> {
+        Options::parse(env::args().skip(1))
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+                "--level" => options.level = Some(parse_value(&arg, args.next())?),
+                "--hint" => options.hint = Some(Duration::from_secs(parse_value(&arg, args.next())?)),
+                "--record" => options.record = Some(parse_value(&arg, args.next())?),
+                "--replay" => options.replay = Some(parse_value(&arg, args.next())?),
+                "--size" => {
+                    let (width, height) = parse_size(&arg, args.next(), USAGE)?;
+                    options.board.width = width;
+                    options.board.height = height;
+                },
+                "--colors" => options.board.colors = parse_colors(&arg, args.next(), USAGE)?,
+                "--loop" => options.board.loop_effect = parse_value(&arg, args.next())?,
+                "--moves" | "--time" => options.mode = parse_mode(&arg, args.next(), options.mode, USAGE)?,
+                "--goal" => {
+                    let goal: Goal = parse_value(&arg, args.next())?;
+                    if options.goals.iter().any(|other| other.color == goal.color) {
+                        return Err(format!("only one goal per color can be given\n{}", USAGE));
+                    }
+                    options.goals.push(goal);
+                },
+                _ => return Err(format!("unknown argument `{}`\n{}", arg, USAGE)),
+            }
+        }
+
+        if let Some(goal) = options.goals.iter().find(|goal| !options.board.colors.contains(&goal.color)) {
+            return Err(format!("goal color {} is not on the board\n{}", goal.color, USAGE));
+        }
+
+        let custom = options.board != Config::default() || options.mode != Mode::Endless || !options.goals.is_empty();
+        if options.level.is_some() && custom {
+            return Err(format!("a level file sets the board, mode and goals itself\n{}", USAGE));
+        }
+
+        let game = custom || options.level.is_some() || options.seed.is_some() || options.record.is_some();
+        if options.replay.is_some() && game {
+            return Err(format!("a replay sets the whole game itself\n{}", USAGE));
+        }
+
+        Ok(options)
+    }
+
+    /// The game described by the board, mode and goal flags.
+    pub fn custom_level(&self) -> Level {
+        Level::random(self.board.clone(), self.mode, self.goals.clone())
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    cats::args::parse_value(name, value, USAGE)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cats::engine::{Color, LoopEffect};
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_seed() {
+        assert_eq!(parse(&[]).unwrap(), Options::default());
+        assert_eq!(parse(&["--seed", "42"]).unwrap().seed, Some(42));
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "x"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn parses_size() {
+        let board = parse(&["--size", "7x9"]).unwrap().board;
+        assert_eq!((board.width, board.height), (7, 9));
+        assert!(parse(&["--size"]).is_err());
+        assert!(parse(&["--size", "7"]).is_err());
+        assert!(parse(&["--size", "7x"]).is_err());
+        assert!(parse(&["--size", "1x5"]).is_err());
+        assert!(parse(&["--size", "5x17"]).is_err());
+    }
+
+    #[test]
+    fn parses_moves() {
+        assert_eq!(parse(&[]).unwrap().mode, Mode::Endless);
+        assert_eq!(parse(&["--moves", "25"]).unwrap().mode, Mode::Moves(25));
+        assert!(parse(&["--moves", "0"]).is_err());
+        assert!(parse(&["--moves", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parses_time() {
+        assert_eq!(parse(&["--time", "60"]).unwrap().mode, Mode::Timed(Duration::from_secs(60)));
+        assert!(parse(&["--time", "0"]).is_err());
+        assert!(parse(&["--time", "60", "--moves", "10"]).is_err());
+    }
+
+    #[test]
+    fn parses_goals() {
+        let options = parse(&["--goal", "red:20", "--goal", "blue:15", "--moves", "25"]).unwrap();
+        assert_eq!(options.goals, vec![Goal::new(Color::Red, 20), Goal::new(Color::Blue, 15)]);
+        assert!(parse(&["--goal", "red"]).is_err());
+        assert!(parse(&["--goal", "red:2", "--goal", "red:3"]).is_err());
+        assert!(parse(&["--colors", "3", "--goal", "red:2"]).is_err());
+    }
+
+    #[test]
+    fn parses_level() {
+        let options = parse(&["--level", "assets/levels/01-warm-up.toml", "--seed", "3"]).unwrap();
+        assert_eq!(options.level, Some(PathBuf::from("assets/levels/01-warm-up.toml")));
+        assert!(parse(&["--level", "a.toml", "--moves", "3"]).is_err());
+        assert!(parse(&["--size", "6x6", "--level", "a.toml"]).is_err());
+    }
+
+    #[test]
+    fn custom_level_matches_flags() {
+        let level = parse(&["--size", "3x4", "--moves", "9"]).unwrap().custom_level();
+        assert_eq!((level.board.width, level.board.height), (3, 4));
+        assert_eq!(level.mode, Mode::Moves(9));
+        assert_eq!(level.layout.len(), 3);
+        assert!(level.layout.iter().all(|column| column.len() == 4));
+    }
+
+    #[test]
+    fn parses_loop_effect() {
+        assert_eq!(parse(&[]).unwrap().board.loop_effect, LoopEffect::None);
+        assert_eq!(parse(&["--loop", "bombs"]).unwrap().board.loop_effect, LoopEffect::Bombs);
+        assert!(parse(&["--loop", "boom"]).is_err());
+        assert!(parse(&["--loop", "clear", "--level", "a.toml"]).is_err());
+    }
+
+    #[test]
+    fn parses_replays() {
+        let options = parse(&["--record", "game.toml", "--level", "a.toml"]).unwrap();
+        assert_eq!(options.record, Some(PathBuf::from("game.toml")));
+        assert_eq!(parse(&["--replay", "game.toml"]).unwrap().replay, Some(PathBuf::from("game.toml")));
+        assert!(parse(&["--replay"]).is_err());
+        assert!(parse(&["--replay", "game.toml", "--seed", "3"]).is_err());
+        assert!(parse(&["--replay", "game.toml", "--moves", "3"]).is_err());
+        assert!(parse(&["--replay", "game.toml", "--record", "again.toml"]).is_err());
+    }
+
+    #[test]
+    fn parses_hint_delay() {
+        assert_eq!(parse(&[]).unwrap().hint, None);
+        assert_eq!(parse(&["--hint", "3"]).unwrap().hint, Some(Duration::from_secs(3)));
+        assert_eq!(parse(&["--hint", "0"]).unwrap().hint, Some(Duration::from_secs(0)));
+        assert!(parse(&["--hint", "soon"]).is_err());
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse(&[]).unwrap().board.colors, Color::DEFAULT.to_vec());
+        assert_eq!(parse(&["--colors", "3"]).unwrap().board.colors, vec![Color::Purple, Color::Blue, Color::Green]);
+        assert_eq!(parse(&["--colors", "8"]).unwrap().board.colors, Color::ALL.to_vec());
+        assert_eq!(parse(&["--colors", "red,gray"]).unwrap().board.colors, vec![Color::Red, Color::Gray]);
+        assert!(parse(&["--colors", "9"]).is_err());
+        assert!(parse(&["--colors", "1"]).is_err());
+        assert!(parse(&["--colors", "red,red"]).is_err());
+        assert!(parse(&["--colors", "red,pink"]).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! Replays: a level, a seed and every input on the board with the game time it happened at.
+//! Boards only take their dots from the seed, so playing the inputs back gives the same game,
+//! down to the score.
+//!
+//! ```toml
+//! version = 1
+//! seed = "42"
+//! score = "37"          # what the game scored, to check a replay against
+//! length = 61000        # game time in milliseconds when the recording stopped
+//! level = "moves = 20\nlayout = ..."
+//! events = ["1200 start 0 1", "1350 extend 1 1", "1500 commit"]
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{Cell, Session, Step, Turn};
+use crate::level::{Level, LevelError};
+use crate::scores;
+
+const VERSION: u32 = 1;
+
+/// An input on the board.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Action {
+    Start(Cell),
+    Extend(Cell),
+    /// Takes the last dot off the chain.
+    Retract,
+    /// Drops the chain.
+    Cancel,
+    Commit,
+    Undo,
+}
+
+impl Action {
+    /// Applies the action to `session`. Returns whether it changed anything, and the turn
+    /// if it committed a chain.
+    pub fn apply(self, session: &mut Session) -> (bool, Option<Turn>) {
+        match self {
+            Action::Start(cell) => (session.start_chain(cell), None),
+            Action::Extend(cell) => (session.extend_chain(cell) != Step::None, None),
+            Action::Retract => (session.retract_chain(), None),
+            Action::Cancel => (session.cancel_chain(), None),
+            Action::Commit => {
+                let changed = !session.board().chain().is_empty();
+                (changed, session.commit_chain())
+            },
+            Action::Undo => (session.undo(), None),
+        }
+    }
+}
+
+/// An action and the game time it happened at.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Event {
+    pub at: Duration,
+    pub action: Action,
+}
+
+/// `<milliseconds> start|extend <x> <y>`, or `<milliseconds> retract|cancel|commit|undo`.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at = self.at.as_millis();
+
+        match self.action {
+            Action::Start(cell) => write!(f, "{} start {} {}", at, cell.x, cell.y),
+            Action::Extend(cell) => write!(f, "{} extend {} {}", at, cell.x, cell.y),
+            Action::Retract => write!(f, "{} retract", at),
+            Action::Cancel => write!(f, "{} cancel", at),
+            Action::Commit => write!(f, "{} commit", at),
+            Action::Undo => write!(f, "{} undo", at),
+        }
+    }
+}
+
+impl std::str::FromStr for Event {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let invalid = || format!("invalid event `{}`", text);
+        let number = |word: &str| word.parse::<u64>().map_err(|_| invalid());
+
+        let at = Duration::from_millis(number(words.first().ok_or_else(invalid)?)?);
+        let action = match words[1..] {
+            ["start", x, y] => Action::Start(Cell::new(number(x)? as usize, number(y)? as usize)),
+            ["extend", x, y] => Action::Extend(Cell::new(number(x)? as usize, number(y)? as usize)),
+            ["retract"] => Action::Retract,
+            ["cancel"] => Action::Cancel,
+            ["commit"] => Action::Commit,
+            ["undo"] => Action::Undo,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Event { at, action })
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Corrupt(String),
+    Level(LevelError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "{}", error),
+            ReplayError::Corrupt(message) => write!(f, "corrupt replay: {}", message),
+            ReplayError::Level(error) => write!(f, "level of the replay: {}", error),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReplayFile {
+    version: u32,
+    #[serde(with = "crate::serde_u64")]
+    seed: u64,
+    #[serde(with = "crate::serde_u64")]
+    score: u64,
+    length: u64,
+    level: String,
+    events: Vec<String>,
+}
+
+/// A recorded game.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Replay {
+    pub level: Level,
+    pub seed: u64,
+    pub events: Vec<Event>,
+    /// Score at the end of the recording.
+    pub score: u64,
+    /// Game time at the end of the recording.
+    pub length: Duration,
+}
+
+impl Replay {
+    /// An empty recording of `level` played on `seed`.
+    pub fn new(level: Level, seed: u64) -> Self {
+        Replay {
+            level,
+            seed,
+            events: Vec::new(),
+            score: 0,
+            length: Duration::from_secs(0),
+        }
+    }
+
+    /// The game as it was before the first event.
+    pub fn session(&self) -> Session {
+        self.level.session(self.seed)
+    }
+
+    pub fn record(&mut self, at: Duration, action: Action) {
+        self.events.push(Event { at, action });
+    }
+
+    /// Notes where the recorded game got to, for `verify`.
+    pub fn finish(&mut self, session: &Session) {
+        self.score = session.score();
+        self.length = session.elapsed();
+    }
+
+    /// Plays every event without a window and returns the game at the end of the recording.
+    pub fn play(&self) -> Session {
+        let mut session = self.session();
+        let mut player = Player::new(self.events.clone());
+
+        player.advance(&mut session, self.length, |session, action| { action.apply(session); });
+
+        session
+    }
+
+    /// Whether playing the events scores what was recorded.
+    pub fn verify(&self) -> bool {
+        self.play().score() == self.score
+    }
+
+    pub fn to_toml(&self) -> String {
+        let file = ReplayFile {
+            version: VERSION,
+            seed: self.seed,
+            score: self.score,
+            length: self.length.as_millis() as u64,
+            level: self.level.to_toml(),
+            events: self.events.iter().map(Event::to_string).collect(),
+        };
+
+        toml::to_string(&file).expect("a replay always serializes")
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, ReplayError> {
+        let file: ReplayFile = toml::from_str(source).map_err(|error| ReplayError::Corrupt(error.to_string()))?;
+
+        if file.version != VERSION {
+            return Err(ReplayError::Corrupt(format!("unknown version {}", file.version)));
+        }
+
+        let events = file.events.iter()
+            .map(|event| event.parse())
+            .collect::<Result<Vec<Event>, _>>()
+            .map_err(ReplayError::Corrupt)?;
+
+        Ok(Replay {
+            level: Level::parse(&file.level).map_err(ReplayError::Level)?,
+            seed: file.seed,
+            events,
+            score: file.score,
+            length: Duration::from_millis(file.length),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        Replay::from_toml(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        scores::write_file(path, &self.to_toml())?;
+
+        Ok(())
+    }
+}
+
+/// Feeds recorded events back into a session as its clock reaches them.
+#[derive(Debug, Clone)]
+pub struct Player {
+    events: Vec<Event>,
+    next: usize,
+}
+
+impl Player {
+    pub fn new(events: Vec<Event>) -> Self {
+        Player { events, next: 0 }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next == self.events.len()
+    }
+
+    /// Runs the session clock up to `until`, handing every event due on the way to `apply`
+    /// at the game time it was recorded at.
+    pub fn advance<F: FnMut(&mut Session, Action)>(&mut self, session: &mut Session, until: Duration, mut apply: F) {
+        while let Some(event) = self.events.get(self.next).filter(|event| event.at <= until) {
+            session.tick(event.at.checked_sub(session.elapsed()).unwrap_or_default());
+            apply(session, event.action);
+            self.next += 1;
+        }
+
+        session.tick(until.checked_sub(session.elapsed()).unwrap_or_default());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::engine::Mode;
+
+    /// Plays greedy pairs with some time between them, recording as it goes.
+    fn recorded(level: &str) -> Replay {
+        let mut replay = Replay::new(Level::parse(level).unwrap(), 5);
+        let mut session = replay.session();
+
+        for _ in 0..12 {
+            session.tick(Duration::from_millis(700));
+
+            let board = session.board();
+            let pair = board.cells()
+                .flat_map(|cell| board.cells().map(move |other| (cell, other)))
+                .find(|&(cell, other)| cell.is_sibling(other) && board.color(cell).is_some() && board.color(cell) == board.color(other));
+
+            if let Some((cell, other)) = pair {
+                [Action::Start(cell), Action::Extend(other), Action::Commit].iter().for_each(|&action| {
+                    replay.record(session.elapsed(), action);
+                    action.apply(&mut session);
+                });
+            }
+        }
+
+        session.tick(Duration::from_millis(300));
+        replay.finish(&session);
+        replay
+    }
+
+    #[test]
+    fn events_roundtrip_through_text() {
+        let events = [
+            Event { at: Duration::from_millis(1200), action: Action::Start(Cell::new(0, 1)) },
+            Event { at: Duration::from_millis(1350), action: Action::Extend(Cell::new(15, 1)) },
+            Event { at: Duration::from_millis(1400), action: Action::Retract },
+            Event { at: Duration::from_millis(1450), action: Action::Cancel },
+            Event { at: Duration::from_millis(0), action: Action::Commit },
+            Event { at: Duration::from_millis(1600), action: Action::Undo },
+        ];
+
+        events.iter().for_each(|event| assert_eq!(event.to_string().parse(), Ok(*event)));
+        assert!("12 start 1".parse::<Event>().is_err());
+        assert!("start 1 2".parse::<Event>().is_err());
+        assert!("-4 commit".parse::<Event>().is_err());
+    }
+
+    #[test]
+    fn replays_score_what_was_recorded() {
+        let replay = recorded("width = 6\nheight = 6\n");
+
+        assert!(replay.score > 0);
+        assert!(replay.verify());
+
+        let mut tampered = replay.clone();
+        tampered.score += 1;
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn replays_run_out_the_clock() {
+        let replay = recorded("width = 5\nheight = 5\ntime = 5\n");
+        let session = replay.play();
+
+        assert_eq!(session.mode(), Mode::Timed(Duration::from_secs(5)));
+        assert!(session.is_over());
+        assert!(replay.verify());
+    }
+
+    #[test]
+    fn roundtrips_through_toml() {
+        let replay = recorded("name = \"Walls\"\nmoves = 8\nloop = \"clear\"\nlayout = '''\n. . . .\n. # . .\n. . . .\n'''\n");
+        let loaded = Replay::from_toml(&replay.to_toml()).unwrap();
+
+        assert_eq!(loaded, replay);
+        assert!(loaded.verify());
+        assert!(matches!(Replay::from_toml("version = 1"), Err(ReplayError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,359 @@
+//! The game in progress, saved to `$XDG_DATA_HOME/cats/save.toml` when the window is closed.
+//!
+//! Every save starts with `version`. New fields with a default keep the version, since old saves
+//! load without them. Other changes to the format put the version up, and the struct for the old
+//! version stays with a conversion to the new one, so old saves still load.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::board::{MIN_SIZE, MAX_SIZE};
+use crate::engine::{Board, Cell, Color, Config, Goal, Layout, LoopEffect, Mode, Progress, Session, Tile};
+use crate::scores::{self, Key};
+
+const VERSION: u32 = 1;
+/// More random numbers than any game draws. Loading replays them one by one, so a damaged
+/// count above this would take ages.
+const MAX_DRAWS: u64 = 1 << 30;
+
+/// A game to pick up later: the session and what to call it.
+#[derive(Debug, Clone)]
+pub struct SavedGame {
+    pub name: String,
+    /// High score table the game goes into once it ends.
+    pub key: Key,
+    /// Scored with the `Rules` of its mode after loading.
+    pub session: Session,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Corrupt(String),
+    /// Saved by a newer version of the game.
+    Version(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "{}", error),
+            SaveError::Corrupt(message) => write!(f, "corrupt save: {}", message),
+            SaveError::Version(version) => write!(f, "save version {} is newer than this game", version),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// Version 1 of the save file.
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    name: String,
+    mode: String,
+    #[serde(with = "crate::serde_u64")]
+    score: u64,
+    combo: u32,
+    moves: u32,
+    /// Game time in milliseconds.
+    elapsed: u64,
+    /// Goals as `color:count`.
+    goals: Vec<String>,
+    /// Dots cleared so far as `color:count`, in the order the colors were first cleared.
+    cleared: Vec<String>,
+    /// Turns taken back so far, missing in saves from before undo.
+    #[serde(default)]
+    undos: u32,
+    /// Missing when the game can undo without limit, or went by its mode's limit before undo was saved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    undo_limit: Option<u32>,
+    key: Key,
+    board: BoardFile,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BoardFile {
+    colors: Vec<String>,
+    #[serde(rename = "loop")]
+    loop_effect: String,
+    #[serde(with = "crate::serde_u64")]
+    seed: u64,
+    #[serde(with = "crate::serde_u64")]
+    draws: u64,
+    /// One string per row, written like a level layout without the spaces.
+    rows: Vec<String>,
+}
+
+impl SavedGame {
+    pub fn to_toml(&self) -> String {
+        let session = &self.session;
+        let board = session.board();
+        let progress = session.progress();
+
+        let rows = (0..board.height())
+            .map(|y| (0..board.width()).map(|x| match board.color(Cell::new(x, y)) {
+                Some(color) => color.symbol(),
+                None => '#',
+            }).collect())
+            .collect();
+
+        let file = SaveFile {
+            version: VERSION,
+            name: self.name.clone(),
+            mode: session.mode().to_string(),
+            score: progress.score,
+            combo: progress.combo,
+            moves: progress.moves,
+            elapsed: progress.elapsed.as_millis() as u64,
+            goals: session.goals().iter().map(Goal::to_string).collect(),
+            cleared: progress.cleared.iter().map(|(color, count)| format!("{}:{}", color, count)).collect(),
+            undos: progress.undos,
+            undo_limit: session.undo_limit(),
+            key: self.key.clone(),
+            board: BoardFile {
+                colors: board.colors().iter().map(Color::to_string).collect(),
+                loop_effect: board.loop_effect().to_string(),
+                seed: board.seed(),
+                draws: board.draws(),
+                rows,
+            },
+        };
+
+        toml::to_string(&file).expect("a save always serializes")
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, SaveError> {
+        let header: Header = toml::from_str(source).map_err(corrupt)?;
+
+        match header.version {
+            1 => toml::from_str::<SaveFile>(source).map_err(corrupt)?.into_game(),
+            version => Err(SaveError::Version(version)),
+        }
+    }
+
+    /// The saved game at `path`, `None` when there is none.
+    pub fn load(path: &Path) -> Result<Option<Self>, SaveError> {
+        match fs::read_to_string(path) {
+            Ok(source) => SavedGame::from_toml(&source).map(Some),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        scores::write_file(path, &self.to_toml())?;
+
+        Ok(())
+    }
+
+    /// Deletes the save at `path`, if there is one.
+    pub fn remove(path: &Path) -> Result<(), SaveError> {
+        match fs::remove_file(path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl SaveFile {
+    fn into_game(self) -> Result<SavedGame, SaveError> {
+        let mode: Mode = self.mode.parse().map_err(SaveError::Corrupt)?;
+        let colors = self.board.colors.iter()
+            .map(|name| name.parse::<Color>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(SaveError::Corrupt)?;
+        let loop_effect: LoopEffect = self.board.loop_effect.parse().map_err(SaveError::Corrupt)?;
+        let goals = self.goals.iter()
+            .map(|goal| goal.parse::<Goal>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(SaveError::Corrupt)?;
+        let cleared = self.cleared.iter()
+            .map(|count| cleared(count))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if colors.is_empty() {
+            return Err(SaveError::Corrupt("a board needs at least one color".to_string()));
+        }
+
+        if self.board.draws > MAX_DRAWS {
+            return Err(SaveError::Corrupt(format!("{} random numbers drawn, more than a game gets through", self.board.draws)));
+        }
+
+        let layout = layout(&self.board.rows, &colors)?;
+        let config = Config { width: layout.len(), height: layout[0].len(), colors, loop_effect };
+        let board = Board::with_layout(&config, &layout, self.board.seed).with_draws(self.board.draws);
+        let progress = Progress {
+            score: self.score,
+            combo: self.combo,
+            moves: self.moves,
+            elapsed: Duration::from_millis(self.elapsed),
+            cleared,
+            undos: self.undos,
+        };
+        let session = Session::with_board(board, mode)
+            .with_goals(goals)
+            .with_undo_limit(self.undo_limit.or_else(|| mode.undo_limit()))
+            .with_progress(progress);
+
+        Ok(SavedGame {
+            name: self.name,
+            key: self.key,
+            session,
+        })
+    }
+}
+
+/// Columns of tiles from the saved rows.
+fn layout(rows: &[String], colors: &[Color]) -> Result<Layout, SaveError> {
+    let width = rows.first().map_or(0, |row| row.chars().count());
+    let sizes = MIN_SIZE..=MAX_SIZE;
+
+    if !sizes.contains(&width) || !sizes.contains(&rows.len()) || rows.iter().any(|row| row.chars().count() != width) {
+        return Err(SaveError::Corrupt("the board is not a rectangle of a valid size".to_string()));
+    }
+
+    let rows = rows.iter()
+        .map(|row| row.chars().map(|symbol| match symbol {
+            '#' => Ok(Tile::Wall),
+            symbol => match Color::from_symbol(symbol) {
+                Some(color) if colors.contains(&color) => Ok(Tile::Dot(color)),
+                _ => Err(SaveError::Corrupt(format!("unexpected dot `{}` on the board", symbol))),
+            },
+        }).collect::<Result<Vec<_>, _>>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((0..width).map(|x| rows.iter().map(|row| row[x]).collect()).collect())
+}
+
+fn cleared(text: &str) -> Result<(Color, u32), SaveError> {
+    let mut parts = text.splitn(2, ':');
+    let color = parts.next().and_then(|name| name.parse().ok());
+    let count = parts.next().and_then(|count| count.parse().ok());
+
+    color.zip(count).ok_or_else(|| SaveError::Corrupt(format!("invalid cleared count `{}`", text)))
+}
+
+fn corrupt(error: toml::de::Error) -> SaveError {
+    SaveError::Corrupt(error.to_string())
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    scores::data_dir().map(|dir| dir.join("save.toml"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::level::Level;
+
+    fn played_game() -> SavedGame {
+        let level = Level::parse("name = \"Walls\"\nmoves = 30\ngoals = { red = 40 }\nloop = \"clear\"\nundo = 2\nlayout = '''\n. . . .\n. # . .\n. . . .\n'''\n").unwrap();
+        let mut session = level.session(u64::MAX - 3);
+
+        for _ in 0..5 {
+            let board = session.board();
+            let pair = board.cells()
+                .flat_map(|cell| board.cells().map(move |other| (cell, other)))
+                .find(|&(cell, other)| cell.is_sibling(other) && board.color(cell).is_some() && board.color(cell) == board.color(other));
+
+            if let Some((cell, other)) = pair {
+                session.start_chain(cell);
+                session.extend_chain(other);
+                session.commit_chain();
+            }
+        }
+        session.undo();
+        session.tick(Duration::from_millis(12_345));
+
+        SavedGame { name: level.name.clone(), key: Key::for_level(&level, None), session }
+    }
+
+    fn snapshot(session: &Session) -> (Progress, Mode, Vec<Goal>, Vec<Option<Color>>, u64, u64) {
+        let board = session.board();
+        (
+            session.progress(),
+            session.mode(),
+            session.goals().to_vec(),
+            board.cells().map(|cell| board.color(cell)).collect(),
+            board.seed(),
+            board.draws(),
+        )
+    }
+
+    #[test]
+    fn roundtrips_a_game() {
+        let saved = played_game();
+        let loaded = SavedGame::from_toml(&saved.to_toml()).unwrap();
+
+        assert_eq!(loaded.name, "Walls");
+        assert_eq!(loaded.key, saved.key);
+        assert_eq!(snapshot(&loaded.session), snapshot(&saved.session));
+        assert!(loaded.session.board().is_wall(Cell::new(1, 1)));
+        assert_eq!(loaded.session.board().loop_effect(), LoopEffect::Clear);
+        assert_eq!(loaded.session.undos_left(), Some(1));
+    }
+
+    #[test]
+    fn loaded_game_plays_on_the_same() {
+        let saved = played_game();
+        let mut original = saved.session.clone();
+        let mut loaded = SavedGame::from_toml(&saved.to_toml()).unwrap().session;
+
+        for session in [&mut original, &mut loaded].iter_mut() {
+            session.start_chain(Cell::new(0, 0));
+            session.extend_chain(Cell::new(0, 1));
+            session.commit_chain();
+        }
+
+        assert_eq!(snapshot(&loaded), snapshot(&original));
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_damage() {
+        let saved = played_game();
+        let source = saved.to_toml();
+        let draws = format!("draws = \"{}\"", saved.session.board().draws());
+
+        assert!(matches!(SavedGame::from_toml(&source.replace("version = 1", "version = 2")), Err(SaveError::Version(2))));
+        assert!(matches!(SavedGame::from_toml("version = 1\n"), Err(SaveError::Corrupt(_))));
+        assert!(matches!(SavedGame::from_toml("nothing"), Err(SaveError::Corrupt(_))));
+        assert!(matches!(SavedGame::from_toml(&source.replace("moves 30", "jumps 3")), Err(SaveError::Corrupt(_))));
+        assert!(matches!(SavedGame::from_toml(&source.replace(&draws, "draws = \"18446744073709551615\"")), Err(SaveError::Corrupt(_))));
+    }
+
+    #[test]
+    fn saves_loads_and_removes() {
+        let dir = std::env::temp_dir().join(format!("cats-save-{}", std::process::id()));
+        let path = dir.join("save.toml");
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(SavedGame::load(&path).unwrap().is_none());
+
+        played_game().save(&path).unwrap();
+        assert!(SavedGame::load(&path).unwrap().is_some());
+
+        SavedGame::remove(&path).unwrap();
+        SavedGame::remove(&path).unwrap();
+        assert!(SavedGame::load(&path).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,356 @@
+//! High scores, kept in `$XDG_DATA_HOME/cats/scores.toml` (`~/.local/share/cats/scores.toml` by default).
+//!
+//! Every combination of level, mode and seed has its own top ten. Games on a random seed share
+//! the table of their level and mode; a seed given on purpose makes a table of its own.
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::LoopEffect;
+use crate::level::Level;
+
+pub const TABLE_SIZE: usize = 10;
+const VERSION: u32 = 1;
+
+/// Which table a game belongs to.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Key {
+    pub level: String,
+    pub mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::serde_u64::option")]
+    pub seed: Option<u64>,
+}
+
+impl Key {
+    /// Key for a game of `level`, `seed` only when it was picked by the player.
+    pub fn for_level(level: &Level, seed: Option<u64>) -> Self {
+        let name = if level.name.is_empty() {
+            let colors: String = level.board.colors.iter().map(|color| color.symbol()).collect();
+            match level.board.loop_effect {
+                LoopEffect::None => format!("{}x{} {}", level.board.width, level.board.height, colors),
+                effect => format!("{}x{} {} {}", level.board.width, level.board.height, colors, effect),
+            }
+        } else {
+            level.name.clone()
+        };
+
+        Key { level: name, mode: level.mode.to_string(), seed }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} - {}", self.level, self.mode)?;
+
+        if let Some(seed) = self.seed {
+            write!(f, " #{}", seed)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A finished game.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(with = "crate::serde_u64")]
+    pub score: u64,
+    /// When the game ended, in seconds since the Unix epoch.
+    pub played_at: u64,
+    /// Game time, in seconds.
+    pub duration: u64,
+    pub moves: u32,
+    #[serde(with = "crate::serde_u64")]
+    pub seed: u64,
+}
+
+impl Entry {
+    /// The day the game was played, as `YYYY-MM-DD` in UTC.
+    pub fn date(&self) -> String {
+        let (year, month, day) = civil_date(self.played_at / 86_400);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.duration)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Table {
+    #[serde(flatten)]
+    pub key: Key,
+    /// Best first.
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScoreFile {
+    version: u32,
+    #[serde(default)]
+    tables: Vec<Table>,
+}
+
+#[derive(Debug)]
+pub enum ScoreError {
+    Io(io::Error),
+    /// The file is not a score file this version understands.
+    Corrupt(String),
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoreError::Io(error) => write!(f, "{}", error),
+            ScoreError::Corrupt(message) => write!(f, "corrupt high score file: {}", message),
+        }
+    }
+}
+
+impl Error for ScoreError {}
+
+impl From<io::Error> for ScoreError {
+    fn from(error: io::Error) -> Self {
+        ScoreError::Io(error)
+    }
+}
+
+/// Every table, and the file they are saved to.
+#[derive(Debug, Default, PartialEq)]
+pub struct HighScores {
+    path: Option<PathBuf>,
+    tables: Vec<Table>,
+}
+
+impl HighScores {
+    /// Scores that are never saved, for when there is no data directory.
+    pub fn in_memory() -> Self {
+        HighScores::default()
+    }
+
+    /// Reads the tables at `path`; a missing file is just an empty store.
+    pub fn load(path: &Path) -> Result<Self, ScoreError> {
+        let mut scores = HighScores { path: Some(path.to_path_buf()), tables: Vec::new() };
+
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(scores),
+            Err(error) => return Err(error.into()),
+        };
+
+        let file: ScoreFile = toml::from_str(&source).map_err(|error| ScoreError::Corrupt(error.to_string()))?;
+        if file.version != VERSION {
+            return Err(ScoreError::Corrupt(format!("unknown version {}", file.version)));
+        }
+
+        scores.tables = file.tables;
+
+        Ok(scores)
+    }
+
+    /// Like `load`, but never fails: a corrupt file is moved aside to `scores.toml.corrupt`
+    /// and the game starts with empty tables. The error is returned to be reported.
+    pub fn open(path: &Path) -> (Self, Option<ScoreError>) {
+        match HighScores::load(path) {
+            Ok(scores) => (scores, None),
+            Err(error) => {
+                if let ScoreError::Corrupt(_) = error {
+                    let _ = fs::rename(path, path.with_extension("toml.corrupt"));
+                }
+
+                (HighScores { path: Some(path.to_path_buf()), tables: Vec::new() }, Some(error))
+            },
+        }
+    }
+
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    pub fn table(&self, key: &Key) -> &[Entry] {
+        self.tables.iter().find(|table| table.key == *key).map_or(&[], |table| &table.entries)
+    }
+
+    /// Adds a finished game, returns its place counting from 0 if it made the top ten.
+    pub fn record(&mut self, key: &Key, entry: Entry) -> Option<usize> {
+        let index = match self.tables.iter().position(|table| table.key == *key) {
+            Some(index) => index,
+            None => {
+                self.tables.push(Table { key: key.clone(), entries: Vec::new() });
+                self.tables.len() - 1
+            },
+        };
+        let entries = &mut self.tables[index].entries;
+
+        let place = entries.iter().position(|other| entry.score > other.score).unwrap_or(entries.len());
+        if place >= TABLE_SIZE {
+            return None;
+        }
+
+        entries.insert(place, entry);
+        entries.truncate(TABLE_SIZE);
+
+        Some(place)
+    }
+
+    /// Writes the tables through a temporary file, so a crash never leaves half a file behind.
+    pub fn save(&self) -> Result<(), ScoreError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let file = ScoreFile { version: VERSION, tables: self.tables.clone() };
+        let source = toml::to_string(&file).map_err(|error| ScoreError::Corrupt(error.to_string()))?;
+
+        write_file(path, &source)?;
+
+        Ok(())
+    }
+}
+
+/// Writes `source` next to `path` first and then moves it in place, creating the directory if needed.
+pub(crate) fn write_file(path: &Path, source: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, source)?;
+    fs::rename(&temporary, path)
+}
+
+/// `$XDG_DATA_HOME/cats`, or `~/.local/share/cats` when it is not set.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_DATA_HOME").filter(|dir| Path::new(dir).is_absolute()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".local").join("share"),
+    };
+
+    Some(base.join("cats"))
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("scores.toml"))
+}
+
+/// Year, month and day of the given day since the Unix epoch.
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(score: u64) -> Entry {
+        Entry { score, played_at: 0, duration: 60, moves: 10, seed: 1 }
+    }
+
+    fn key(seed: Option<u64>) -> Key {
+        Key { level: "Warm up".to_string(), mode: "moves 15".to_string(), seed }
+    }
+
+    fn temporary_path(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("cats-scores-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("scores.toml")
+    }
+
+    #[test]
+    fn keeps_the_top_ten_best_first() {
+        let mut scores = HighScores::in_memory();
+
+        assert_eq!(scores.record(&key(None), entry(5)), Some(0));
+        assert_eq!(scores.record(&key(None), entry(9)), Some(0));
+        assert_eq!(scores.record(&key(None), entry(7)), Some(1));
+        (0..10).for_each(|_| { scores.record(&key(None), entry(8)); });
+
+        let table = scores.table(&key(None));
+        assert_eq!(table.len(), TABLE_SIZE);
+        assert_eq!(table[0].score, 9);
+        assert_eq!(table[9].score, 8);
+        assert_eq!(scores.record(&key(None), entry(1)), None);
+        assert!(scores.table(&key(Some(3))).is_empty());
+    }
+
+    #[test]
+    fn keys_describe_the_game() {
+        let level = Level::parse("width = 6\nheight = 4\ncolors = [\"red\", \"blue\"]\ntime = 60\n").unwrap();
+        let key = Key::for_level(&level, Some(7));
+
+        assert_eq!(key.level, "6x4 RB");
+        assert_eq!(key.to_string(), "6x4 RB - time 60 #7");
+
+        let level = Level::parse("name = \"Rush\"\nwidth = 6\nheight = 4\nloop = \"bombs\"\n").unwrap();
+        assert_eq!(Key::for_level(&level, None).to_string(), "Rush - endless");
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let path = temporary_path("roundtrip");
+        let (mut scores, error) = HighScores::open(&path);
+        assert!(error.is_none());
+
+        scores.record(&key(None), entry(12));
+        scores.record(&key(Some(4)), entry(3));
+        scores.save().unwrap();
+
+        assert_eq!(HighScores::load(&path).unwrap(), scores);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn keeps_seeds_that_do_not_fit_a_toml_integer() {
+        let path = temporary_path("seeds");
+        let mut scores = HighScores::load(&path).unwrap();
+
+        scores.record(&key(Some(u64::MAX)), Entry { seed: u64::MAX, ..entry(1) });
+        scores.save().unwrap();
+
+        assert_eq!(HighScores::load(&path).unwrap(), scores);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn moves_a_corrupt_file_aside() {
+        let path = temporary_path("corrupt");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "version = [").unwrap();
+
+        let (scores, error) = HighScores::open(&path);
+
+        assert!(scores.tables().is_empty());
+        assert!(matches!(error, Some(ScoreError::Corrupt(_))));
+        assert!(!path.exists());
+        assert!(path.with_extension("toml.corrupt").exists());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(entry(0).date(), "1970-01-01");
+        assert_eq!(Entry { played_at: 951_782_400, ..entry(0) }.date(), "2000-02-29");
+        assert_eq!(Entry { played_at: 1_792_281_600, ..entry(0) }.date(), "2026-10-18");
+    }
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use cats::engine::Mode;
+
+use crate::game::menu::{Menu, Navigation};
+
+/// The games the new game menu offers, on the board of the current one.
+const MODES: [Mode; 5] = [
+    Mode::Endless,
+    Mode::Moves(20),
+    Mode::Moves(40),
+    Mode::Timed(Duration::from_secs(60)),
+    Mode::Timed(Duration::from_secs(120)),
+];
+
+/// What a menu button does.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+enum Item {
+    Resume,
+    Restart,
+    NewGame,
+    Settings,
+    Hints,
+    Mode(Mode),
+    Back,
+    Quit,
+}
+
+/// What the player picked in the menus, for the caller to carry out. The menus are closed
+/// by then, except for settings.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Choice {
+    Restart,
+    NewGame(Mode),
+    /// Hints turned on or off.
+    Hints(bool),
+    Quit,
+}
+
+/// The menus open over the game, the top one takes the input. The game stands still
+/// while any is open.
+#[derive(Debug, Default)]
+pub struct Screens {
+    stack: Vec<Menu<Item>>,
+    hints: bool,
+}
+
+impl Screens {
+    pub fn is_open(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    /// Opens the pause menu. `restart` is whether the game can be started over, `hints`
+    /// whether hints are on.
+    pub fn pause(&mut self, restart: bool, hints: bool) {
+        let items = [
+            Some(("RESUME", Item::Resume)),
+            Some(("RESTART", Item::Restart)).filter(|_| restart),
+            Some(("NEW GAME", Item::NewGame)),
+            Some(("SETTINGS", Item::Settings)),
+            Some(("QUIT", Item::Quit)),
+        ];
+
+        self.hints = hints;
+        self.stack = vec![menu("PAUSED", items.iter().flatten().copied())];
+    }
+
+    pub fn navigate(&mut self, navigation: Navigation, size: (u32, u32)) -> Option<Choice> {
+        let top = self.stack.last_mut()?;
+
+        let item = match navigation {
+            Navigation::Back => {
+                self.stack.pop();
+                return None;
+            },
+            Navigation::Quit => return Some(Choice::Quit),
+            navigation => top.navigate(navigation, size)?,
+        };
+
+        match item {
+            Item::Resume => self.stack.clear(),
+            Item::Restart => {
+                self.stack.clear();
+                return Some(Choice::Restart);
+            },
+            Item::NewGame => {
+                let modes: Vec<(String, Item)> = MODES.iter().map(|&mode| (mode_label(mode), Item::Mode(mode))).collect();
+                self.stack.push(Menu::new("NEW GAME", modes.into_iter().chain(Some(("BACK".to_string(), Item::Back))).collect()));
+            },
+            Item::Settings => {
+                let items = [(hints_label(self.hints), Item::Hints), ("BACK", Item::Back)];
+                self.stack.push(menu("SETTINGS", items.iter().copied()));
+            },
+            Item::Hints => {
+                self.hints = !self.hints;
+                top.relabel(hints_label(self.hints).to_string());
+                return Some(Choice::Hints(self.hints));
+            },
+            Item::Mode(mode) => {
+                self.stack.clear();
+                return Some(Choice::NewGame(mode));
+            },
+            Item::Back => {
+                self.stack.pop();
+            },
+            Item::Quit => return Some(Choice::Quit),
+        }
+
+        None
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>) {
+        if let Some(top) = self.stack.last() {
+            top.render(canvas);
+        }
+    }
+}
+
+fn menu<'a, I: Iterator<Item = (&'a str, Item)>>(title: &str, items: I) -> Menu<Item> {
+    Menu::new(title, items.map(|(label, item)| (label.to_string(), item)).collect())
+}
+
+fn hints_label(hints: bool) -> &'static str {
+    if hints { "HINTS ON" } else { "HINTS OFF" }
+}
+
+fn mode_label(mode: Mode) -> String {
+    match mode {
+        Mode::Endless => "ENDLESS".to_string(),
+        Mode::Moves(moves) => format!("{} MOVES", moves),
+        Mode::Timed(time) => format!("{} SECONDS", time.as_secs()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SIZE: (u32, u32) = (400, 400);
+
+    fn choose(screens: &mut Screens, downs: usize) -> Option<Choice> {
+        (0..downs).for_each(|_| { screens.navigate(Navigation::Down, SIZE); });
+        screens.navigate(Navigation::Choose, SIZE)
+    }
+
+    #[test]
+    fn resume_and_back_close_the_menu() {
+        let mut screens = Screens::default();
+        assert!(!screens.is_open());
+
+        screens.pause(true, true);
+        assert_eq!(choose(&mut screens, 0), None);
+        assert!(!screens.is_open());
+
+        screens.pause(true, true);
+        assert_eq!(screens.navigate(Navigation::Back, SIZE), None);
+        assert!(!screens.is_open());
+    }
+
+    #[test]
+    fn new_games_pick_a_mode() {
+        let mut screens = Screens::default();
+
+        screens.pause(false, true);
+        assert_eq!(choose(&mut screens, 1), None);
+        assert_eq!(choose(&mut screens, 5), None);
+        assert!(screens.is_open());
+        assert_eq!(choose(&mut screens, 0), None);
+        assert_eq!(choose(&mut screens, 1), Some(Choice::NewGame(Mode::Moves(20))));
+        assert!(!screens.is_open());
+
+        screens.pause(true, true);
+        assert_eq!(choose(&mut screens, 1), Some(Choice::Restart));
+    }
+
+    #[test]
+    fn settings_toggle_hints() {
+        let mut screens = Screens::default();
+
+        screens.pause(true, false);
+        assert_eq!(choose(&mut screens, 3), None);
+        assert_eq!(choose(&mut screens, 0), Some(Choice::Hints(true)));
+        assert_eq!(choose(&mut screens, 0), Some(Choice::Hints(false)));
+        assert!(screens.is_open());
+        assert_eq!(screens.navigate(Navigation::Quit, SIZE), Some(Choice::Quit));
+    }
+}
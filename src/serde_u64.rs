@@ -0,0 +1,97 @@
+//! TOML integers are signed, so `u64`s that can use every bit, such as seeds, are written as strings.
+//! Reading takes either form. Use with `#[serde(with = "crate::serde_u64")]`.
+
+use std::fmt;
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    deserializer.deserialize_any(U64Visitor)
+}
+
+struct U64Visitor;
+
+impl<'de> Visitor<'de> for U64Visitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an unsigned 64-bit integer or a string holding one")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+        if value < 0 {
+            return Err(E::invalid_value(de::Unexpected::Signed(value), &self));
+        }
+
+        Ok(value as u64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+        value.parse().map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// The same for optional fields, which also need `default` and `skip_serializing_if = "Option::is_none"`.
+pub mod option {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+        super::deserialize(deserializer).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Seeded {
+        #[serde(with = "crate::serde_u64")]
+        seed: u64,
+    }
+
+    #[test]
+    fn roundtrips_every_value() {
+        [0, 42, i64::MAX as u64 + 1, u64::MAX].iter().for_each(|&seed| {
+            let source = toml::to_string(&Seeded { seed }).unwrap();
+            assert_eq!(toml::from_str::<Seeded>(&source).unwrap(), Seeded { seed });
+        });
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct MaybeSeeded {
+        #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::serde_u64::option")]
+        seed: Option<u64>,
+    }
+
+    #[test]
+    fn roundtrips_optional_values() {
+        [None, Some(u64::MAX)].iter().for_each(|&seed| {
+            let source = toml::to_string(&MaybeSeeded { seed }).unwrap();
+            assert_eq!(toml::from_str::<MaybeSeeded>(&source).unwrap(), MaybeSeeded { seed });
+        });
+    }
+
+    #[test]
+    fn reads_plain_integers() {
+        assert_eq!(toml::from_str::<Seeded>("seed = 7").unwrap(), Seeded { seed: 7 });
+        assert!(toml::from_str::<Seeded>("seed = -7").is_err());
+        assert!(toml::from_str::<Seeded>("seed = \"x\"").is_err());
+    }
+}